@@ -1,7 +1,7 @@
 use std::hint::black_box;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use simd_needle::{Finder, FinderTrait, SearchAlgo};
+use simd_needle::{bmh_find_all, bmh_search, Finder, FinderTrait, SearchAlgo};
 
 // Pattern that appears multiple times
 const PATTERN: &str = "hello";
@@ -69,6 +69,35 @@ fn bench_bmh_large(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_bmh_small, bench_bmh_medium, bench_bmh_large,);
+// Periodic needle matching at every period, the worst case for restarting
+// bmh_search after each match
+fn bench_bmh_periodic_find_all(c: &mut Criterion) {
+    let data = b"abc".repeat(1024 * 1024 / 3); // ~1MB
+    let needle = b"abcabcabc".repeat(4);
+
+    let mut group = c.benchmark_group("bmh_periodic_find_all");
+    group.bench_function("galil", |b| {
+        b.iter(|| black_box(bmh_find_all(black_box(&data), &needle).len()));
+    });
+    group.bench_function("restart_per_match", |b| {
+        b.iter(|| {
+            let (haystack, mut pos, mut count) = (black_box(&data[..]), 0, 0);
+            while let Some(i) = bmh_search(&haystack[pos..], &needle) {
+                count += 1;
+                pos += i + 1;
+            }
+            black_box(count)
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_bmh_small,
+    bench_bmh_medium,
+    bench_bmh_large,
+    bench_bmh_periodic_find_all,
+);
 
 criterion_main!(benches);
@@ -0,0 +1,48 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use simd_needle::{simd_search, Finder, FinderTrait, SearchAlgo, DEFAULT_BUF_SIZE};
+
+fn generate_test_data(size: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+    for i in 0..size {
+        // Create some repeating patterns to make the search meaningful
+        data.push(((i / 64) % 256) as u8);
+    }
+    data
+}
+
+fn bench_tail_copy(c: &mut Criterion) {
+    let data = generate_test_data(100 * 1024 * 1024); // 100MB
+
+    // Neither needle ever matches, so every buffer ends with a tail copy and a refill
+    let short_needle = b"never matches".to_vec();
+    let long_needle = vec![b'x'; DEFAULT_BUF_SIZE / 2];
+
+    let mut group = c.benchmark_group("finder_tail_copy");
+    group.sample_size(10);
+    group.bench_function("slice_baseline", |b| {
+        b.iter(|| black_box(simd_search(black_box(&data), &short_needle)));
+    });
+    for (name, needle) in [
+        ("short_needle", &short_needle),
+        ("long_needle", &long_needle),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut finder = black_box(Finder::with_algorithm(
+                    &data[..],
+                    needle.clone(),
+                    SearchAlgo::Simd,
+                ))
+                .expect("Search failed");
+                black_box(finder.next().is_none())
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tail_copy);
+
+criterion_main!(benches);
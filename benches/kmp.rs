@@ -1,7 +1,9 @@
 use std::hint::black_box;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use simd_needle::{Finder, FinderTrait, SearchAlgo};
+use simd_needle::{
+    kmp_prefix_table, kmp_search, kmp_search_with_table, Finder, FinderTrait, SearchAlgo,
+};
 
 // Pattern that appears multiple times
 const PATTERN: &str = "hello";
@@ -69,6 +71,42 @@ fn bench_kmp_large(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_kmp_small, bench_kmp_medium, bench_kmp_large,);
+// Many short searches for a long needle, the pattern of a Finder reporting dense
+// matches: rebuilding the prefix table dominates each call
+fn bench_kmp_prefix_table_reuse(c: &mut Criterion) {
+    let needle: Vec<u8> = (0..512).map(|i| (i % 7) as u8).collect();
+    let mut data = generate_test_data(1024);
+    data.extend_from_slice(&needle);
+    let mut group = c.benchmark_group("kmp_prefix_table_reuse");
+
+    group.bench_function("rebuild_per_call", |b| {
+        b.iter(|| {
+            for _ in 0..64 {
+                black_box(kmp_search(black_box(&data), black_box(&needle)));
+            }
+        });
+    });
+    group.bench_function("prebuilt_table", |b| {
+        let table = kmp_prefix_table(&needle);
+        b.iter(|| {
+            for _ in 0..64 {
+                black_box(kmp_search_with_table(
+                    black_box(&data),
+                    black_box(&needle),
+                    &table,
+                ));
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_kmp_small,
+    bench_kmp_medium,
+    bench_kmp_large,
+    bench_kmp_prefix_table_reuse,
+);
 
 criterion_main!(benches);
@@ -23,8 +23,7 @@ fn bench_memchr_small(c: &mut Criterion) {
 
     c.bench_function("memchr_small", |b| {
         b.iter(|| {
-            let mut finder = black_box(memmem::find_iter(&small_data, PATTERN));
-            while let Some(pos) = finder.next() {
+            for pos in black_box(memmem::find_iter(&small_data, PATTERN)) {
                 let _ = black_box(pos);
             }
         });
@@ -36,8 +35,7 @@ fn bench_memchr_medium(c: &mut Criterion) {
 
     c.bench_function("memchr_medium", |b| {
         b.iter(|| {
-            let mut finder = black_box(memmem::find_iter(&medium_data, PATTERN));
-            while let Some(pos) = finder.next() {
+            for pos in black_box(memmem::find_iter(&medium_data, PATTERN)) {
                 let _ = black_box(pos);
             }
         });
@@ -49,8 +47,7 @@ fn bench_memchr_large(c: &mut Criterion) {
 
     c.bench_function("memchr_large", |b| {
         b.iter(|| {
-            let mut finder = black_box(memmem::find_iter(&large_data, PATTERN));
-            while let Some(pos) = finder.next() {
+            for pos in black_box(memmem::find_iter(&large_data, PATTERN)) {
                 let _ = black_box(pos);
             }
         });
@@ -73,8 +70,7 @@ fn bench_memchr_haystacks(c: &mut Criterion) {
 
     c.bench_function("memchr_haystacks", |b| {
         b.iter(|| {
-            let mut finder = black_box(memmem::find_iter(&haystacks_data, &PATTERN));
-            while let Some(pos) = finder.next() {
+            for pos in black_box(memmem::find_iter(&haystacks_data, &PATTERN)) {
                 let _ = black_box(pos);
             }
         });
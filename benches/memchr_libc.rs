@@ -2,7 +2,6 @@ use std::hint::black_box;
 use std::path::PathBuf;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use libc;
 use rand::Rng;
 use walkdir::WalkDir;
 
@@ -1,7 +1,7 @@
 use std::hint::black_box;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use simd_needle::{Finder, FinderTrait, SearchAlgo};
+use simd_needle::{naive_search, Finder, FinderTrait, SearchAlgo};
 
 // Pattern that appears multiple times
 const PATTERN: &str = "hello";
@@ -69,11 +69,40 @@ fn bench_naive_large(c: &mut Criterion) {
     });
 }
 
+// Short needles on an in-memory slice, where per-position overhead dominates.
+// The windows baseline is the previous naive_search implementation.
+fn bench_naive_search_short(c: &mut Criterion) {
+    let data = generate_test_data(1024 * 1024); // 1MB
+    let mut group = c.benchmark_group("naive_search_short");
+
+    // Every byte of the data occurs in runs of 64, so "run" is full of candidates
+    for (name, needle) in [
+        ("absent", &b"ab"[..]),
+        ("hello", b"hello"),
+        ("run", b"\x0f\x0f\x0f\x11"),
+    ] {
+        group.bench_function(format!("first_byte_scan/{}", name), |b| {
+            b.iter(|| black_box(naive_search(black_box(&data), black_box(needle))));
+        });
+        group.bench_function(format!("windows_baseline/{}", name), |b| {
+            b.iter(|| {
+                let (haystack, needle) = (black_box(&data[..]), black_box(needle));
+                black_box(
+                    (0..=haystack.len() - needle.len())
+                        .find(|&i| &haystack[i..i + needle.len()] == needle),
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_naive_small,
     bench_naive_medium,
     bench_naive_large,
+    bench_naive_search_short,
 );
 
 criterion_main!(benches);
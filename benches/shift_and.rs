@@ -0,0 +1,32 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use simd_needle::{bmh_search, shift_and_search};
+
+// 10-byte needle, short enough for a single u64 Shift-And state
+const PATTERN: &[u8] = b"needle1234";
+
+fn generate_test_data(size: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+    for i in 0..size {
+        data.push(((i / 64) % 256) as u8);
+    }
+    data.extend_from_slice(PATTERN);
+    data
+}
+
+fn bench_shift_and_vs_bmh(c: &mut Criterion) {
+    let data = generate_test_data(1024 * 1024); // 1MB, match at the end
+
+    let mut group = c.benchmark_group("shift_and_vs_bmh_10_byte_needle");
+    group.bench_function("shift_and", |b| {
+        b.iter(|| black_box(shift_and_search(black_box(&data), black_box(PATTERN))));
+    });
+    group.bench_function("bmh", |b| {
+        b.iter(|| black_box(bmh_search(black_box(&data), black_box(PATTERN))));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_shift_and_vs_bmh);
+criterion_main!(benches);
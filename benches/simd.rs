@@ -3,7 +3,7 @@ use std::{hint::black_box, io::BufReader};
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::Rng;
-use simd_needle::{Finder, FinderTrait, SearchAlgo};
+use simd_needle::{naive_search, simd_search, Finder, FinderTrait, SearchAlgo};
 use walkdir::WalkDir;
 
 // Pattern that appears multiple times
@@ -72,6 +72,91 @@ fn bench_simd_large(c: &mut Criterion) {
     });
 }
 
+fn bench_simd_pair_large(c: &mut Criterion) {
+    let large_data = generate_test_data(10 * 1024 * 1024); // 10MB
+
+    // Two-byte marker that only shows up where the repeating pattern wraps around
+    let pair = [0xff, 0x00];
+
+    let mut group = c.benchmark_group("simd_pair_large");
+    group.bench_function("simd_search", |b| {
+        b.iter(|| {
+            let mut pos = 0;
+            while let Some(i) = simd_search(black_box(&large_data[pos..]), &pair) {
+                pos += i + 1;
+            }
+            black_box(pos)
+        });
+    });
+    group.bench_function("naive_search", |b| {
+        b.iter(|| {
+            let mut pos = 0;
+            while let Some(i) = naive_search(black_box(&large_data[pos..]), &pair) {
+                pos += i + 1;
+            }
+            black_box(pos)
+        });
+    });
+    group.finish();
+}
+
+fn bench_simd_two_anchor(c: &mut Criterion) {
+    // First byte of the needle shows up every 9 bytes, the last one never does
+    let repetitive_data = b"aXXXXXXXc".repeat(1024 * 1024 / 9); // ~1MB
+    let needle = b"aXXXXXXXb";
+
+    let mut group = c.benchmark_group("simd_two_anchor");
+    group.bench_function("simd_search", |b| {
+        b.iter(|| black_box(simd_search(black_box(&repetitive_data), needle)));
+    });
+    group.bench_function("naive_search", |b| {
+        b.iter(|| black_box(naive_search(black_box(&repetitive_data), needle)));
+    });
+    group.finish();
+}
+
+fn bench_simd_dense_candidates(c: &mut Criterion) {
+    // Both anchors of the needle match at every position, so every byte is a candidate
+    // that fails verification
+    let saturated_data = vec![b'a'; 1024 * 1024]; // 1MB
+    let needle = b"aaaXaaaa";
+
+    let mut group = c.benchmark_group("simd_dense_candidates");
+    group.bench_function("simd_search", |b| {
+        b.iter(|| black_box(simd_search(black_box(&saturated_data), needle)));
+    });
+    group.finish();
+}
+
+fn bench_simd_rare_anchor(c: &mut Criterion) {
+    // English text where the needle starts and ends with a space, the most common byte
+    let text_data =
+        std::fs::read("./haystacks/opensubtitles/en-huge.txt").expect("Failed to read en-huge.txt");
+    let needle = b" quixotic ";
+
+    let mut group = c.benchmark_group("simd_rare_anchor");
+    group.bench_function("simd_search", |b| {
+        b.iter(|| black_box(simd_search(black_box(&text_data), needle)));
+    });
+    group.finish();
+}
+
+fn bench_simd_zero_run(c: &mut Criterion) {
+    // Zero-heavy binary data: runs of up to 7 zeros between non-zero bytes, the 8 byte
+    // run only shows up at the end
+    let mut zero_data: Vec<u8> = (0..1024 * 1024)
+        .map(|i: usize| if i % 8 == 7 { 0xff } else { 0 })
+        .collect(); // 1MB
+    zero_data.extend_from_slice(&[0; 8]);
+    let needle = [0u8; 8];
+
+    let mut group = c.benchmark_group("simd_zero_run");
+    group.bench_function("simd_search", |b| {
+        b.iter(|| black_box(simd_search(black_box(&zero_data), &needle)));
+    });
+    group.finish();
+}
+
 fn bench_simd_haystacks(c: &mut Criterion) {
     let dir_path = PathBuf::from("./haystacks");
     let walker = WalkDir::new(&dir_path).follow_links(false).into_iter();
@@ -110,6 +195,11 @@ criterion_group!(
     bench_simd_small,
     bench_simd_medium,
     bench_simd_large,
+    bench_simd_pair_large,
+    bench_simd_two_anchor,
+    bench_simd_dense_candidates,
+    bench_simd_rare_anchor,
+    bench_simd_zero_run,
     bench_simd_haystacks,
 );
 
@@ -1,5 +1,5 @@
+use std::hint::black_box;
 use std::path::PathBuf;
-use std::{hint::black_box, io::BufReader};
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::Rng;
@@ -72,6 +72,24 @@ fn bench_mmap_large(c: &mut Criterion) {
     });
 }
 
+fn bench_mmap_dense_matches(c: &mut Criterion) {
+    // The needle back to back, a match every 16 bytes
+    let needle = b"needle_0123456_";
+    let dense_data = [&needle[..], b" "].concat().repeat(1024 * 1024 / 16); // 1MB
+    let temp_path = "/tmp/dense_data.bin";
+    std::fs::write(temp_path, &dense_data).expect("Failed to write temp file");
+    let finder = MmapFinder::new(temp_path, needle.to_vec()).expect("Search failed");
+
+    let mut group = c.benchmark_group("mmap_dense_matches");
+    for algo in [SearchAlgo::Bmh, SearchAlgo::Kmp, SearchAlgo::ShiftAnd] {
+        group.bench_function(algo.to_string(), |b| {
+            b.iter(|| black_box(finder.find_all(algo).count()));
+        });
+    }
+    group.finish();
+    std::fs::remove_file(temp_path).ok();
+}
+
 fn bench_mmap_haystacks(c: &mut Criterion) {
     let dir_path = PathBuf::from("./haystacks");
     let walker = WalkDir::new(&dir_path).follow_links(false).into_iter();
@@ -102,6 +120,7 @@ criterion_group!(
     bench_mmap_small,
     bench_mmap_medium,
     bench_mmap_large,
+    bench_mmap_dense_matches,
     bench_mmap_haystacks,
 );
 
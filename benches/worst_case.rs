@@ -0,0 +1,56 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use simd_needle::{Finder, FinderTrait, SearchAlgo};
+
+const SIZE: usize = 1024 * 1024; // 1MB
+
+/// BMH worst case: the last needle byte always matches and the mismatch is found
+/// at the very first needle byte, after which the shift is a single byte
+fn bmh_worst_case() -> (Vec<u8>, Vec<u8>) {
+    let mut needle = vec![b'a'; 32];
+    needle[0] = b'b';
+    (vec![b'a'; SIZE], needle)
+}
+
+/// Periodic needle in a periodic haystack that keeps almost matching, KMP falls
+/// back through its prefix table on every near miss
+fn kmp_periodic() -> (Vec<u8>, Vec<u8>) {
+    let mut needle = b"ab".repeat(16);
+    needle.push(b'c');
+    (b"ab".repeat(SIZE / 2), needle)
+}
+
+/// Both SIMD anchors (first and last byte) match at every position and the
+/// mismatch sits near the end, so every candidate is verified
+fn common_anchors() -> (Vec<u8>, Vec<u8>) {
+    let mut needle = vec![b'a'; 16];
+    needle[14] = b'b';
+    (vec![b'a'; SIZE], needle)
+}
+
+fn bench_worst_case(c: &mut Criterion) {
+    for (name, (haystack, needle)) in [
+        ("bmh_worst_case", bmh_worst_case()),
+        ("kmp_periodic", kmp_periodic()),
+        ("common_anchors", common_anchors()),
+    ] {
+        let mut group = c.benchmark_group(format!("worst_case/{}", name));
+        group.sample_size(10);
+        for &algo in SearchAlgo::ALL {
+            group.bench_with_input(BenchmarkId::from_parameter(algo), &algo, |b, &algo| {
+                b.iter(|| {
+                    let finder =
+                        black_box(Finder::with_algorithm(&haystack[..], needle.clone(), algo))
+                            .expect("Search failed");
+                    black_box(finder.count())
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_worst_case);
+
+criterion_main!(benches);
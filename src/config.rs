@@ -0,0 +1,115 @@
+//! Reusable parsers for search configuration strings, such as algorithm choices
+
+use std::path::Path;
+use std::str::FromStr;
+
+use glob::Pattern;
+
+use crate::search::{Algorithm, ParseAlgorithmError};
+
+/// Comma separated list of algorithms, e.g. `naive,bmh,simd`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgoList(pub Vec<Algorithm>);
+
+impl FromStr for AlgoList {
+    type Err = ParseAlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|name| name.trim().parse())
+            .collect::<Result<_, _>>()
+            .map(AlgoList)
+    }
+}
+
+/// Mapping from glob patterns to the algorithm used for matching paths
+///
+/// Parsed from comma separated `glob=algorithm` pairs, e.g. `*.txt=naive,*.bin=simd`.
+/// The pairs keep their order, so when several patterns match a path the first one
+/// given wins.
+#[derive(Debug, Clone, Default)]
+pub struct AlgoMap(Vec<(Pattern, Algorithm)>);
+
+impl AlgoMap {
+    /// Returns the algorithm for `path`, if any pattern matches it
+    ///
+    /// The first matching pattern, in the order they were given, decides.
+    /// Patterns are tried against the whole path, then against its file name alone, so
+    /// `app.*` also routes `/var/log/app.log`.
+    pub fn get_for_path(&self, path: &Path) -> Option<Algorithm> {
+        let file_name = path.file_name().map(Path::new);
+        self.0
+            .iter()
+            .find(|(pattern, _)| {
+                pattern.matches_path(path) || file_name.is_some_and(|f| pattern.matches_path(f))
+            })
+            .map(|&(_, algo)| algo)
+    }
+}
+
+/// Error returned when parsing an `AlgoMap`
+#[derive(Debug)]
+pub enum ParseAlgoMapError {
+    /// An entry is not of the form `glob=algorithm`
+    InvalidMapping(String),
+    InvalidGlob(glob::PatternError),
+    InvalidAlgorithm(ParseAlgorithmError),
+}
+
+impl std::error::Error for ParseAlgoMapError {}
+
+impl std::fmt::Display for ParseAlgoMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseAlgoMapError::InvalidMapping(entry) => {
+                write!(f, "Invalid mapping {:?}, expected glob=algorithm", entry)
+            }
+            ParseAlgoMapError::InvalidGlob(e) => write!(f, "Invalid glob: {}", e),
+            ParseAlgoMapError::InvalidAlgorithm(e) => e.fmt(f),
+        }
+    }
+}
+
+impl FromStr for AlgoMap {
+    type Err = ParseAlgoMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|entry| {
+                let (glob, algo) = entry
+                    .split_once('=')
+                    .ok_or_else(|| ParseAlgoMapError::InvalidMapping(entry.to_string()))?;
+                let pattern = Pattern::new(glob.trim()).map_err(ParseAlgoMapError::InvalidGlob)?;
+                let algo = algo
+                    .trim()
+                    .parse()
+                    .map_err(ParseAlgoMapError::InvalidAlgorithm)?;
+                Ok((pattern, algo))
+            })
+            .collect::<Result<_, _>>()
+            .map(AlgoMap)
+    }
+}
+
+/// Splits a needle of literal alternatives such as `foo|bar|baz` on `|`
+///
+/// `\|` stands for a literal `|` and `\\` for a literal backslash, any other
+/// backslash is kept as is. Empty alternatives are kept, so callers can reject them
+/// the way they reject an empty needle.
+///
+/// # Returns
+/// The alternatives in the order given, ready for `Finder::with_needles`
+pub fn split_alternatives(pattern: &[u8]) -> Vec<Vec<u8>> {
+    let mut alternatives = vec![Vec::new()];
+    let mut bytes = pattern.iter().copied().peekable();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' if matches!(bytes.peek(), Some(b'|' | b'\\')) => {
+                alternatives.last_mut().unwrap().extend(bytes.next())
+            }
+            b'|' => alternatives.push(Vec::new()),
+            _ => alternatives.last_mut().unwrap().push(b),
+        }
+    }
+    alternatives
+}
@@ -0,0 +1,144 @@
+use std::io::{self, Read};
+
+use crate::finder::validate_needle;
+use crate::search::{search, Algorithm};
+use crate::{Finder, FinderError, DEFAULT_BUF_SIZE};
+
+/// A match reported by `ContextFinder`, with the bytes around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMatch {
+    /// Absolute offset of the match in the stream, `u64` like `Finder::positions_u64` so
+    /// streams past 4GB are reported correctly on 32-bit targets
+    pub offset: u64,
+    /// Up to `context` bytes preceding the match, fewer near the start of the stream
+    pub before: Vec<u8>,
+    /// Up to `context` bytes following the match, fewer near the end of the stream
+    pub after: Vec<u8>,
+}
+
+/// A streaming finder that reports `context` bytes before and after every match
+///
+/// Keeps a rolling window of the stream: the last `context` bytes before the next
+/// possible match are retained, and a match is only reported once the `context`
+/// bytes after it have been read (or the stream ended). Back-to-back matches whose
+/// contexts overlap each get their own copy of the shared bytes.
+pub struct ContextFinder<R: Read> {
+    haystack: R,
+    needle: Vec<u8>,
+    context: usize,
+    /// Retained bytes of the stream, `window[0]` is at absolute offset `window_start`
+    window: Vec<u8>,
+    window_start: u64,
+    /// Absolute offset of the next position that can start a match
+    scan_pos: u64,
+    eof: bool,
+    algo: Algorithm,
+}
+
+impl<R: Read> Finder<R> {
+    /// Creates a finder that reports each match with up to `context` surrounding bytes
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `context` - Number of bytes to report before and after each match
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_context(
+        haystack: R,
+        needle: Vec<u8>,
+        context: usize,
+        algo: Option<Algorithm>,
+    ) -> Result<ContextFinder<R>, FinderError> {
+        validate_needle(&needle, usize::MAX)?;
+        Ok(ContextFinder {
+            haystack,
+            needle,
+            context,
+            window: Vec::new(),
+            window_start: 0,
+            scan_pos: 0,
+            eof: false,
+            algo: algo.unwrap_or(Algorithm::Naive),
+        })
+    }
+}
+
+impl<R: Read> ContextFinder<R> {
+    /// Number of bytes currently retained, for tests of the window bound
+    #[cfg(test)]
+    pub(crate) fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Appends the next chunk of the stream to the window
+    fn fill(&mut self) -> io::Result<()> {
+        let len = self.window.len();
+        self.window.resize(len + DEFAULT_BUF_SIZE, 0);
+        match self.haystack.read(&mut self.window[len..]) {
+            Ok(n) => {
+                self.window.truncate(len + n);
+                self.eof = n == 0;
+                Ok(())
+            }
+            Err(e) => {
+                self.window.truncate(len);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Iterator implementation that yields each match with its context
+impl<R: Read> Iterator for ContextFinder<R> {
+    type Item = io::Result<ContextMatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let window_end = self.window_start + self.window.len() as u64;
+            let scan_from = (self.scan_pos - self.window_start) as usize;
+            if let Some(i) = search(self.algo, &self.window[scan_from..], &self.needle) {
+                let offset = self.scan_pos + i as u64;
+                let after_end = offset
+                    .saturating_add(self.needle.len() as u64)
+                    .saturating_add(self.context as u64);
+                // Wait for the trailing context unless the stream has ended
+                if after_end <= window_end || self.eof {
+                    let start = (offset - self.window_start) as usize;
+                    let before_start = start.saturating_sub(self.context);
+                    let after_start = start + self.needle.len();
+                    let after_end = (after_end - self.window_start).min(self.window.len() as u64);
+                    let after_end = after_end as usize;
+                    self.scan_pos = offset + 1;
+                    return Some(Ok(ContextMatch {
+                        offset,
+                        before: self.window[before_start..start].to_vec(),
+                        after: self.window[after_start..after_end].to_vec(),
+                    }));
+                }
+                // Nothing before this match can start another one
+                self.scan_pos = offset;
+            } else {
+                if self.eof {
+                    return None;
+                }
+                // Only the last needle.len() - 1 bytes can still start a match
+                self.scan_pos = self
+                    .scan_pos
+                    .max((window_end + 1).saturating_sub(self.needle.len() as u64));
+            }
+
+            // Drop what is neither searchable nor leading context of a future match
+            let keep_from = self
+                .scan_pos
+                .saturating_sub(self.context as u64)
+                .max(self.window_start);
+            self.window
+                .drain(..(keep_from - self.window_start) as usize);
+            self.window_start = keep_from;
+
+            if let Err(e) = self.fill() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
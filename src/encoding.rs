@@ -0,0 +1,52 @@
+use std::str::FromStr;
+
+/// Text encoding a needle is converted to before a byte search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Error returned when parsing an unknown encoding name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEncodingError(String);
+
+impl std::error::Error for ParseEncodingError {}
+
+impl std::fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Unknown encoding {:?}, expected utf8, utf16le or utf16be",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    /// Parses `utf8`, `utf16le` or `utf16be`, case-insensitively
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf8" => Ok(Encoding::Utf8),
+            "utf16le" => Ok(Encoding::Utf16Le),
+            "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(ParseEncodingError(s.to_string())),
+        }
+    }
+}
+
+/// Encodes a textual needle into the bytes it appears as in the haystack
+///
+/// The search itself stays byte oriented, e.g. `"AB"` as UTF-16LE is searched as
+/// `[0x41, 0x00, 0x42, 0x00]`. Characters outside the BMP become surrogate pairs.
+pub fn encode_needle(s: &str, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Utf8 => s.as_bytes().to_vec(),
+        Encoding::Utf16Le => s.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        Encoding::Utf16Be => s.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+    }
+}
@@ -1,8 +1,10 @@
-use std::io::{self, Read};
+#[cfg(any(unix, windows))]
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(any(unix, windows))]
+use std::ops::Range;
 
-#[cfg(target_arch = "x86_64")]
-use crate::search::simd_search_x86_64;
-use crate::search::{bmh_search, kmp_search, naive_search, simd_search, Algorithm};
+use crate::search::{Algorithm, PreparedSearch, SearchFn};
 
 #[derive(Debug)]
 pub enum FinderError {
@@ -10,9 +12,145 @@ pub enum FinderError {
     BufferTooSmall,
 }
 
+impl std::error::Error for FinderError {}
+
+impl std::fmt::Display for FinderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FinderError::EmptyNeedle => f.write_str("Needle is empty"),
+            FinderError::BufferTooSmall => f.write_str("Needle is longer than the buffer"),
+        }
+    }
+}
+
+/// Lets constructors be used with `?` in functions returning `io::Result`
+///
+/// Every variant becomes `io::ErrorKind::InvalidInput`, with the `FinderError` as the
+/// inner error.
+impl From<FinderError> for io::Error {
+    fn from(e: FinderError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Error returned by `Finder::from_env`
+#[derive(Debug)]
+pub enum FromEnvError {
+    /// An environment variable holds an invalid value
+    InvalidVar { name: &'static str, value: String },
+    /// The configured finder was rejected, e.g. a needle longer than the chosen buffer
+    Finder(FinderError),
+}
+
+impl std::error::Error for FromEnvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FromEnvError::InvalidVar { .. } => None,
+            FromEnvError::Finder(e) => Some(e),
+        }
+    }
+}
+
+impl std::fmt::Display for FromEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FromEnvError::InvalidVar { name, value } => {
+                write!(f, "Invalid value {:?} for {}", value, name)
+            }
+            FromEnvError::Finder(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<FinderError> for FromEnvError {
+    fn from(e: FinderError) -> Self {
+        FromEnvError::Finder(e)
+    }
+}
+
+impl From<FromEnvError> for io::Error {
+    fn from(e: FromEnvError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// How a `Finder` treats an empty needle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyNeedlePolicy {
+    /// Reject the needle with `FinderError::EmptyNeedle`
+    #[default]
+    Error,
+    /// Match at every position `0..=len`, end of stream included, like `str::match_indices("")`
+    MatchAll,
+}
+
 /// Default buffer size for the finder (8KB)
+///
+/// Large enough that the `needle.len() - 1` bytes carried between reads are a small
+/// fraction of each read for typical needles, small enough to stay in L1/L2 cache.
 pub const DEFAULT_BUF_SIZE: usize = 8 * 1024;
 
+/// Longest needle accepted by `Finder::new` and `Finder::with_algorithm`
+///
+/// Longer needles need a larger buffer, pass one of at least the needle length to
+/// `Finder::with_buffer_size`.
+pub const MAX_NEEDLE_LEN: usize = DEFAULT_BUF_SIZE;
+
+/// Environment variable naming the algorithm used by `Finder::from_env`
+const ALGO_ENV_VAR: &str = "SIMD_NEEDLE_ALGO";
+
+/// Environment variable holding the buffer size used by `Finder::from_env`
+const BUFSIZE_ENV_VAR: &str = "SIMD_NEEDLE_BUFSIZE";
+
+/// Largest buffer `Finder::with_capacity_hint` allocates for a hint (1MB)
+const MAX_HINTED_BUF_SIZE: usize = 1024 * 1024;
+
+/// Largest buffer `Finder::from_env` accepts from `SIMD_NEEDLE_BUFSIZE` (1GB)
+const MAX_ENV_BUF_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Checks that `needle` can be searched for with a buffer of `buffer_size` bytes
+///
+/// This holds the needle rules shared by the finder constructors returning
+/// `FinderError`. Finders that have no read buffer, like `SliceFinder`, pass
+/// `usize::MAX`. `MmapFinder` has its own error type and mirrors these rules in
+/// `check_needle`, a new rule here needs a matching `MmapFinderError` there.
+///
+/// # Errors
+/// * `FinderError::EmptyNeedle` - If the needle is empty
+/// * `FinderError::BufferTooSmall` - If the needle is longer than the buffer
+pub(crate) fn validate_needle(needle: &[u8], buffer_size: usize) -> Result<(), FinderError> {
+    if needle.is_empty() {
+        return Err(FinderError::EmptyNeedle);
+    }
+    if needle.len() > buffer_size {
+        return Err(FinderError::BufferTooSmall);
+    }
+    Ok(())
+}
+
+/// A match found in a stream, as yielded by `Finder::matches_serializable`
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Match {
+    /// Absolute offset of the first matched byte
+    pub offset: usize,
+    /// Number of matched bytes
+    pub len: usize,
+}
+
+/// Outcome of searching the buffered window, see `Finder::find_in_window`
+enum WindowMatch {
+    Found(usize),
+    /// Match at the end of the buffered data whose end boundary is not known yet
+    Undecided(usize),
+    None,
+}
+
+/// Whether `b` continues a multibyte UTF-8 sequence, i.e. is not the start of a char
+fn is_utf8_continuation(b: u8) -> bool {
+    b & 0xc0 == 0x80
+}
+
 /// A streaming text finder that searches for a needle in a reader
 ///
 /// Implements an iterator that yields positions of matches in the stream.
@@ -21,11 +159,28 @@ pub struct Finder<R: Read> {
     haystack: R,
     needle: Vec<u8>,
     buffer: Vec<u8>,
-    haystack_pos: usize,
+    /// Absolute offset of `buffer[0]`, a `u64` so streams over 4GB work on 32-bit targets
+    haystack_pos: u64,
     buffer_pos: usize,
     buffer_fill_len: usize,
     algo: Algorithm,
     requested_buffer_size: usize,
+    /// Tables of `algo` built once, the search restarts on every buffer window and match
+    prepared: PreparedSearch,
+    /// Skip matches that do not start and end on UTF-8 char boundaries, see
+    /// `Finder::utf8_aligned`
+    utf8_aligned: bool,
+    /// Absolute offset from which no match may start, see `Finder::with_byte_limit`
+    byte_limit: Option<u64>,
+    /// Upper bound on the bytes requested per `read` call, see `Finder::with_read_chunk`
+    read_chunk: Option<usize>,
+    /// User supplied search function replacing `algo`, see `Finder::with_custom`
+    custom: Option<SearchFn>,
+    /// CRC32 of the stream and the absolute offset it covers, see `Finder::with_checksum`
+    #[cfg(feature = "checksum")]
+    checksum: Option<(crc32fast::Hasher, u64)>,
+    /// Set once an empty needle has reported its match at the end of the stream
+    exhausted: bool,
 }
 
 pub trait FinderTrait<R: Read> {
@@ -48,22 +203,22 @@ pub trait FinderTrait<R: Read> {
 impl<R: Read> FinderTrait<R> for Finder<R> {
     /// Creates a new Finder with default buffer size
     ///
+    /// Needles longer than `MAX_NEEDLE_LEN` are rejected with `FinderError::BufferTooSmall`,
+    /// use `with_buffer_size` for those.
+    ///
     /// # Arguments
     /// * `haystack` - The source to read from and search in
     /// * `needle` - Bytes to search for
     /// * `algo` - Optional search algorithm to use, defaults to Naive
     fn new(haystack: R, needle: Vec<u8>, algo: Option<Algorithm>) -> Result<Self, FinderError> {
-        if needle.is_empty() {
-            return Err(FinderError::EmptyNeedle);
-        }
-        if needle.len() > DEFAULT_BUF_SIZE {
-            return Err(FinderError::BufferTooSmall);
-        }
         Self::with_buffer_size(haystack, needle, DEFAULT_BUF_SIZE, algo)
     }
 
     /// Creates a new Finder with custom buffer size
     ///
+    /// Accepts needles up to `buffer_size` bytes, so this is the way to search for
+    /// needles longer than `MAX_NEEDLE_LEN`.
+    ///
     /// # Arguments
     /// * `haystack` - The source to read from and search in
     /// * `needle` - Bytes to search for
@@ -72,111 +227,870 @@ impl<R: Read> FinderTrait<R> for Finder<R> {
     fn with_buffer_size(
         haystack: R,
         needle: Vec<u8>,
-        mut buffer_size: usize,
+        buffer_size: usize,
         algo: Option<Algorithm>,
     ) -> Result<Self, FinderError> {
-        if needle.is_empty() {
-            return Err(FinderError::EmptyNeedle);
-        }
-        if needle.len() > buffer_size {
-            return Err(FinderError::BufferTooSmall);
-        }
-        let requested_buffer_size = buffer_size;
-        buffer_size += needle.len() - 1;
-        Ok(Self {
+        validate_needle(&needle, buffer_size)?;
+        Ok(Self::from_parts(haystack, needle, buffer_size, algo))
+    }
+
+    /// Creates a new Finder with specified algorithm and default buffer size, see `new`
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `algo` - Search algorithm to use
+    fn with_algorithm(haystack: R, needle: Vec<u8>, algo: Algorithm) -> Result<Self, FinderError> {
+        Self::new(haystack, needle, Some(algo))
+    }
+}
+
+impl<R: Read> Finder<R> {
+    /// Builds a finder without validating the needle, which may be empty
+    fn from_parts(
+        haystack: R,
+        needle: Vec<u8>,
+        buffer_size: usize,
+        algo: Option<Algorithm>,
+    ) -> Self {
+        let algo = algo.unwrap_or(Algorithm::Naive);
+        let prepared = PreparedSearch::new(algo, &needle);
+        Self {
             haystack,
+            buffer: vec![0; buffer_size + needle.len().saturating_sub(1)],
             needle,
-            buffer: vec![0; buffer_size],
             haystack_pos: 0,
             buffer_pos: 0,
             buffer_fill_len: 0,
-            algo: algo.unwrap_or(Algorithm::Naive),
-            requested_buffer_size,
-        })
+            algo,
+            requested_buffer_size: buffer_size,
+            prepared,
+            utf8_aligned: false,
+            byte_limit: None,
+            read_chunk: None,
+            custom: None,
+            #[cfg(feature = "checksum")]
+            checksum: None,
+            exhausted: false,
+        }
     }
 
-    /// Creates a new Finder with specified algorithm and default buffer size
+    /// Creates a new Finder that searches each buffer window with a custom function
+    ///
+    /// `f` takes the place of the `Algorithm` dispatch and is called like the built-in
+    /// searches: with a window of the stream and the needle, returning the offset of the
+    /// first match within the window. Matches spanning buffer boundaries are handled by
+    /// the finder as usual. `algorithm()` reports `Naive` for such finders.
     ///
     /// # Arguments
     /// * `haystack` - The source to read from and search in
     /// * `needle` - Bytes to search for
-    /// * `algo` - Search algorithm to use
-    fn with_algorithm(haystack: R, needle: Vec<u8>, algo: Algorithm) -> Result<Self, FinderError> {
-        if needle.is_empty() {
-            return Err(FinderError::EmptyNeedle);
+    /// * `f` - Search function to use
+    pub fn with_custom(haystack: R, needle: Vec<u8>, f: SearchFn) -> Result<Self, FinderError> {
+        let mut finder = Self::new(haystack, needle, None)?;
+        finder.custom = Some(f);
+        Ok(finder)
+    }
+
+    /// Creates a new Finder configured from environment variables
+    ///
+    /// `SIMD_NEEDLE_ALGO` names the algorithm, as accepted by `Algorithm::from_str`, and
+    /// `SIMD_NEEDLE_BUFSIZE` the buffer size in bytes. Unset variables fall back to the
+    /// defaults of `Finder::new`. Meant for experiments without code changes.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    ///
+    /// # Errors
+    /// * `FromEnvError::InvalidVar` - If a variable is set to an unknown algorithm, a
+    ///   value that is not a number or is not valid unicode, or a buffer size above 1GB
+    /// * `FromEnvError::Finder` - Any error of `Finder::with_buffer_size` for the chosen
+    ///   buffer size
+    pub fn from_env(haystack: R, needle: Vec<u8>) -> Result<Self, FromEnvError> {
+        /// Parses the variable `name` if it is set
+        fn parse_var<T: std::str::FromStr>(name: &'static str) -> Result<Option<T>, FromEnvError> {
+            let value = match std::env::var(name) {
+                Ok(value) => value,
+                Err(std::env::VarError::NotPresent) => return Ok(None),
+                Err(std::env::VarError::NotUnicode(value)) => {
+                    let value = value.to_string_lossy().into_owned();
+                    return Err(FromEnvError::InvalidVar { name, value });
+                }
+            };
+            match value.trim().parse() {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(_) => Err(FromEnvError::InvalidVar { name, value }),
+            }
         }
-        if needle.len() > DEFAULT_BUF_SIZE {
-            return Err(FinderError::BufferTooSmall);
+
+        let algo = parse_var::<Algorithm>(ALGO_ENV_VAR)?;
+        let buffer_size = parse_var::<usize>(BUFSIZE_ENV_VAR)?.unwrap_or(DEFAULT_BUF_SIZE);
+        if buffer_size > MAX_ENV_BUF_SIZE {
+            let value = buffer_size.to_string();
+            return Err(FromEnvError::InvalidVar {
+                name: BUFSIZE_ENV_VAR,
+                value,
+            });
         }
-        Self::with_buffer_size(haystack, needle, DEFAULT_BUF_SIZE, Some(algo))
+        Ok(Self::with_buffer_size(haystack, needle, buffer_size, algo)?)
     }
-}
 
-/// Iterator implementation that yields positions of matches in the stream
-/// Returns `io::Result<usize>` indicating the position of each match or potential IO errors
-impl<R: Read> Iterator for Finder<R> {
-    type Item = io::Result<usize>;
+    /// Creates a new Finder that only reports matches on UTF-8 char boundaries
+    ///
+    /// A match is skipped if it starts or ends inside a multibyte UTF-8 sequence of the
+    /// haystack, e.g. a needle given as raw bytes that also occur within a codepoint.
+    /// Checking the end needs the byte after the match, so the finder keeps one more
+    /// byte buffered than usual and holds back a match ending at the end of the buffered
+    /// data until the next read. Offsets are meaningful as char boundaries if the
+    /// haystack is valid UTF-8, other haystacks are only checked for continuation bytes.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn utf8_aligned(
+        haystack: R,
+        needle: Vec<u8>,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let mut finder = Self::new(haystack, needle, algo)?;
+        finder.utf8_aligned = true;
+        // Room for the byte after a match carried along with it
+        finder.buffer.push(0);
+        Ok(finder)
+    }
+
+    /// Creates a new Finder that reads at most `read_chunk` bytes per `read` call
+    ///
+    /// Reads normally ask for all free space in the buffer. Capping them helps with
+    /// latency-sensitive streams and pipes that prefer small reads, and exercises the
+    /// partial read handling. A chunk of 0 is treated as 1, `None` leaves reads uncapped.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `buffer_size` - Size of internal buffer for reading
+    /// * `read_chunk` - Maximum number of bytes requested per `read` call
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_read_chunk(
+        haystack: R,
+        needle: Vec<u8>,
+        buffer_size: usize,
+        read_chunk: Option<usize>,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let mut finder = Self::with_buffer_size(haystack, needle, buffer_size, algo)?;
+        finder.read_chunk = read_chunk.map(|chunk| chunk.max(1));
+        Ok(finder)
+    }
+
+    /// Creates a new Finder that stops searching after the first `limit` bytes
+    ///
+    /// Only matches starting before `limit` are reported, a match straddling the limit
+    /// included. The stream is read no further than needed to complete such matches, at
+    /// most `limit + needle.len() - 1` bytes, so endless readers like pipes that never
+    /// close still end the search.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `limit` - Number of bytes matches may start in
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_byte_limit(
+        haystack: R,
+        needle: Vec<u8>,
+        limit: u64,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let mut finder = Self::new(haystack, needle, algo)?;
+        finder.byte_limit = Some(limit);
+        Ok(finder)
+    }
+
+    /// Creates a new Finder that computes the CRC32 of the stream while searching it
+    ///
+    /// The checksum is fed from the bytes the finder reads anyway, see
+    /// `checksum_so_far`.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    #[cfg(feature = "checksum")]
+    pub fn with_checksum(
+        haystack: R,
+        needle: Vec<u8>,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let mut finder = Self::new(haystack, needle, algo)?;
+        finder.checksum = Some((crc32fast::Hasher::new(), 0));
+        Ok(finder)
+    }
+
+    /// Returns the CRC32 of the stream up to the last reported match
+    ///
+    /// After a match at `pos` this covers the bytes before `pos`, once the iterator
+    /// has returned `None` it covers the whole stream. Returns 0 for finders not
+    /// created with `with_checksum`.
+    #[cfg(feature = "checksum")]
+    pub fn checksum_so_far(&self) -> u32 {
+        self.checksum
+            .as_ref()
+            .map_or(0, |(hasher, _)| hasher.clone().finalize())
+    }
+
+    /// Feeds the stream up to absolute offset `end` into the checksum, if any
+    ///
+    /// Called before bytes are dropped from the buffer, so everything between the
+    /// covered offset and `end` is still buffered.
+    fn checksum_until(&mut self, end: u64) {
+        #[cfg(feature = "checksum")]
+        if let Some((hasher, covered)) = &mut self.checksum {
+            if *covered < end {
+                let start = (*covered - self.haystack_pos) as usize;
+                hasher.update(&self.buffer[start..(end - self.haystack_pos) as usize]);
+                *covered = end;
+            }
+        }
+        #[cfg(not(feature = "checksum"))]
+        let _ = end;
+    }
+
+    /// Creates a new Finder whose reported positions start at `base`
+    ///
+    /// Meant for readers over a window of a larger file: every position, and
+    /// `bytes_consumed`, is `base + local_pos`, i.e. an offset into the larger file.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `base` - Offset of the first byte of `haystack`
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_base_offset(
+        haystack: R,
+        needle: Vec<u8>,
+        base: usize,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let mut finder = Self::new(haystack, needle, algo)?;
+        finder.haystack_pos = base as u64;
+        Ok(finder)
+    }
+
+    /// Creates a new Finder with default buffer size, choosing how an empty needle is treated
+    ///
+    /// With `EmptyNeedlePolicy::Error` this is `Finder::new`. With
+    /// `EmptyNeedlePolicy::MatchAll` an empty needle yields every position from 0 up to
+    /// and including the stream length, non-empty needles are searched as usual.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    /// * `policy` - What to do if `needle` is empty
+    pub fn with_empty_needle_policy(
+        haystack: R,
+        needle: Vec<u8>,
+        algo: Option<Algorithm>,
+        policy: EmptyNeedlePolicy,
+    ) -> Result<Self, FinderError> {
+        if needle.is_empty() && policy == EmptyNeedlePolicy::MatchAll {
+            return Ok(Self::from_parts(haystack, needle, DEFAULT_BUF_SIZE, algo));
+        }
+        Self::new(haystack, needle, algo)
+    }
+
+    /// Creates a new Finder with a buffer sized for a stream of known length
+    ///
+    /// Small streams then fit in a single buffer and are searched after one read.
+    /// The buffer is at least `needle.len()` and at most 1MB, larger streams are
+    /// streamed through a 1MB buffer as usual.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `expected_len` - Expected length of the stream, it does not need to be exact
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_capacity_hint(
+        haystack: R,
+        needle: Vec<u8>,
+        expected_len: usize,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        let buffer_size = expected_len.min(MAX_HINTED_BUF_SIZE).max(needle.len());
+        Self::with_buffer_size(haystack, needle, buffer_size, algo)
+    }
+
+    /// Returns the search algorithm used by this finder
+    pub fn algorithm(&self) -> Algorithm {
+        self.algo
+    }
+
+    /// Returns the buffer size requested at construction
+    ///
+    /// The internal buffer is `needle.len() - 1` bytes larger to carry partial matches.
+    pub fn buffer_size(&self) -> usize {
+        self.requested_buffer_size
+    }
+
+    /// Returns the needle being searched for
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Returns whether the needle occurs anywhere in the stream
+    ///
+    /// Stops at the first match, so the stream is only read as far as needed to find it
+    /// (at most one buffer beyond the match, less for readers returning short reads).
+    pub fn contains(mut self) -> io::Result<bool> {
+        self.next().transpose().map(|found| found.is_some())
+    }
+
+    /// Collects the positions of all matches in the stream
+    ///
+    /// # Returns
+    /// Vector of positions of all matches
+    ///
+    /// # Errors
+    /// Returns the first IO error raised while reading, dropping any matches found before it
+    pub fn collect_all(self) -> io::Result<Vec<usize>> {
+        self.collect()
+    }
+
+    /// Collects the positions of all matches together with the length of the stream
+    ///
+    /// The length is `bytes_consumed` once the stream is exhausted, which lets callers
+    /// check that the whole stream was searched or account for the bytes read.
+    ///
+    /// # Returns
+    /// Positions of all matches and the number of bytes consumed
+    ///
+    /// # Errors
+    /// Returns the first IO error raised while reading, dropping any matches found before it
+    pub fn run(mut self) -> io::Result<(Vec<usize>, usize)> {
+        let positions = self.by_ref().collect::<io::Result<_>>()?;
+        Ok((positions, self.bytes_consumed()))
+    }
+
+    /// Writes the positions of all matches into `out`, reusing its allocation
+    ///
+    /// `out` is cleared first, so it holds only this stream's matches afterwards.
+    ///
+    /// # Errors
+    /// Returns the first IO error raised while reading, `out` then holds the matches
+    /// found before it
+    pub fn positions_into(self, out: &mut Vec<usize>) -> io::Result<()> {
+        out.clear();
+        for pos in self {
+            out.push(pos?);
+        }
+        Ok(())
+    }
+
+    /// Iterates over the positions of matches together with the algorithm that found them
+    ///
+    /// Diagnostics for `Algorithm::Auto`, which reports the concrete algorithm it resolved
+    /// to for this needle. Other finders echo their configured algorithm.
+    #[cfg(feature = "debug")]
+    pub fn positions_with_algo(self) -> impl Iterator<Item = io::Result<(usize, Algorithm)>> {
+        let resolved = self.algo.resolve(&self.needle);
+        self.map(move |pos| pos.map(|pos| (pos, resolved)))
+    }
+
+    /// Iterates over matches together with a copy of the bytes around them
+    ///
+    /// Each item is the match offset and the buffered bytes from up to `window` bytes
+    /// before the match to up to `window` bytes after its end. Only bytes still in the
+    /// buffer are available, so the window is truncated at buffer edges: near the start
+    /// of a refilled buffer only the `needle.len() - 1` carried bytes precede a match, and
+    /// bytes after it may not have been read yet. Use `Finder::with_context` for complete
+    /// context.
+    ///
+    /// # Arguments
+    /// * `window` - Number of bytes wanted on each side of the match
+    pub fn matches_with_window(
+        mut self,
+        window: usize,
+    ) -> impl Iterator<Item = io::Result<(usize, Vec<u8>)>> {
+        let len = self.needle.len();
+        std::iter::from_fn(move || {
+            let pos = match self.next()? {
+                Ok(pos) => pos,
+                Err(e) => return Some(Err(e)),
+            };
+            let start = (pos as u64 - self.haystack_pos) as usize;
+            let end = (start + len)
+                .saturating_add(window)
+                .min(self.buffer_fill_len);
+            let bytes = self.buffer[start.saturating_sub(window)..end].to_vec();
+            Some(Ok((pos, bytes)))
+        })
+    }
+
+    /// Iterates over matches as serializable `Match` records
+    ///
+    /// Meant for pipelines writing matches out with serde, e.g. one JSON object per
+    /// match. IO errors are passed through as they occur.
+    #[cfg(feature = "serde")]
+    pub fn matches_serializable(self) -> impl Iterator<Item = io::Result<Match>> {
+        let len = self.needle.len();
+        self.map(move |pos| pos.map(|offset| Match { offset, len }))
+    }
+
+    /// Coalesces overlapping matches into maximal `(start, end)` byte ranges
+    ///
+    /// `end` is exclusive, so `b"aaaaa"` searched for `b"aa"` yields the single range
+    /// `(0, 5)`. Matches that merely touch, like `b"abab"` searched for `b"ab"`, stay
+    /// separate ranges. A range is reported once the next match starts past its end or
+    /// the stream ends, and IO errors are passed through as they occur.
+    pub fn merged_ranges(mut self) -> impl Iterator<Item = io::Result<(usize, usize)>> {
+        let len = self.needle.len();
+        let mut current: Option<(usize, usize)> = None;
+        std::iter::from_fn(move || loop {
+            match self.next() {
+                Some(Ok(pos)) => match current {
+                    Some((start, end)) if pos < end => current = Some((start, pos + len)),
+                    _ => {
+                        if let Some(range) = current.replace((pos, pos + len)) {
+                            return Some(Ok(range));
+                        }
+                    }
+                },
+                Some(Err(e)) => return Some(Err(e)),
+                None => return current.take().map(Ok),
+            }
+        })
+    }
+
+    /// Groups the positions of matches into batches of `n`
+    ///
+    /// Every batch holds `n` positions except the last, which may be shorter. An IO error
+    /// ends the current batch early: the positions collected before it are yielded
+    /// first, followed by the error as its own item.
+    ///
+    /// # Panics
+    /// Panics if `n` is 0
+    pub fn chunks(mut self, n: usize) -> impl Iterator<Item = io::Result<Vec<usize>>> {
+        assert!(n != 0, "chunk size must be non-zero");
+        let mut error = None;
+        std::iter::from_fn(move || {
+            if let Some(e) = error.take() {
+                return Some(Err(e));
+            }
+            // `n` is only an upper bound, huge values must not reserve memory up front
+            let mut batch = Vec::with_capacity(n.min(1024));
+            while batch.len() < n {
+                match self.next() {
+                    Some(Ok(pos)) => batch.push(pos),
+                    Some(Err(e)) if batch.is_empty() => return Some(Err(e)),
+                    Some(Err(e)) => {
+                        error = Some(e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            (!batch.is_empty()).then_some(Ok(batch))
+        })
+    }
+
+    /// Wraps the finder so the next match can be inspected before it is consumed
+    ///
+    /// Unlike `Iterator::peekable`, the wrapper also offers `peek_offset`.
+    pub fn peekable(self) -> PeekableFinder<R> {
+        PeekableFinder {
+            finder: self,
+            peeked: None,
+        }
+    }
+
+    /// Skips the next `n` bytes of the stream without searching them
+    ///
+    /// Skipping starts at `bytes_consumed`, so matches beginning in the skipped bytes are
+    /// never reported while later positions stay absolute offsets into the stream. The
+    /// bytes are read and discarded through the buffer, so `with_checksum` still covers
+    /// them. Skipping past the end of the stream is not an error, the finder then simply
+    /// yields no more matches.
+    ///
+    /// # Errors
+    /// Returns the IO error raised while reading the skipped bytes
+    pub fn skip_bytes(&mut self, n: usize) -> io::Result<()> {
+        let mut remaining = n;
+        loop {
+            let buffered = (self.buffer_fill_len - self.buffer_pos).min(remaining);
+            self.buffer_pos += buffered;
+            remaining -= buffered;
+            if remaining == 0 {
+                return Ok(());
+            }
+
+            // Buffer is exhausted, read the next part to skip
+            self.checksum_until(self.bytes_consumed_u64());
+            self.haystack_pos += self.buffer_pos as u64;
+            self.buffer_fill_len = 0;
+            self.buffer_pos = 0;
+            match self.read_at(0)? {
+                0 => return Ok(()),
+                n => self.buffer_fill_len = n,
+            }
+        }
+    }
+
+    /// Returns the absolute offset of the next byte to be examined
+    ///
+    /// Every byte before this offset has been read from the haystack and fully searched,
+    /// so a match can only be reported at this offset or later. After a match at `pos` this
+    /// is `pos + 1`; once the iterator has returned `None` it equals the stream length.
+    /// Saturates at `usize::MAX` on 32-bit targets, see `bytes_consumed_u64`.
+    pub fn bytes_consumed(&self) -> usize {
+        usize::try_from(self.bytes_consumed_u64()).unwrap_or(usize::MAX)
+    }
+
+    /// Returns the absolute offset of the next byte to be examined, see `bytes_consumed`
+    pub fn bytes_consumed_u64(&self) -> u64 {
+        self.haystack_pos + self.buffer_pos as u64
+    }
+
+    /// Iterates over the positions of matches as `u64` offsets
+    ///
+    /// Yields the same matches as iterating the finder itself, but positions past 4GB
+    /// are reported as they are on 32-bit targets, where the `usize` iterator returns
+    /// an `InvalidData` error for them instead.
+    pub fn positions_u64(mut self) -> impl Iterator<Item = io::Result<u64>> {
+        std::iter::from_fn(move || self.next_u64())
+    }
+
+    /// Reads from the haystack into the buffer from `start`, honouring `read_chunk` and
+    /// `byte_limit`
+    fn read_at(&mut self, start: usize) -> io::Result<usize> {
+        let mut end = match self.read_chunk {
+            Some(chunk) => start.saturating_add(chunk).min(self.buffer.len()),
+            None => self.buffer.len(),
+        };
+        if let Some(limit) = self.byte_limit {
+            // Past the limit, only bytes completing a match that starts before it are read.
+            // Reading nothing then ends the search like the end of the stream.
+            let readable = limit
+                .saturating_add(self.needle.len() as u64 - 1)
+                .saturating_sub(self.haystack_pos);
+            end = end.min(usize::try_from(readable).unwrap_or(usize::MAX).max(start));
+        }
+        self.haystack.read(&mut self.buffer[start..end])
+    }
+
+    /// Reports every position of the stream for an empty needle, see `EmptyNeedlePolicy`
+    fn next_empty_match(&mut self) -> Option<io::Result<u64>> {
+        if self.exhausted {
+            return None;
+        }
+        if self.buffer_pos >= self.buffer_fill_len {
+            self.haystack_pos += self.buffer_pos as u64;
+            self.buffer_pos = 0;
+            self.buffer_fill_len = 0;
+            match self.read_at(0) {
+                Ok(0) => {
+                    // The empty needle also matches at the end of the stream
+                    self.exhausted = true;
+                    return Some(Ok(self.haystack_pos));
+                }
+                Ok(n) => self.buffer_fill_len = n,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let pos = self.bytes_consumed_u64();
+        self.buffer_pos += 1;
+        Some(Ok(pos))
+    }
+
+    /// Searches `area` with the custom function or the prepared algorithm
+    fn search_in(&self, area: &[u8]) -> Option<usize> {
+        match self.custom {
+            Some(f) => f(area, &self.needle),
+            None => self.prepared.search(area, &self.needle),
+        }
+    }
+
+    /// Finds the next match in the buffered window, as an index into the buffer
+    ///
+    /// With `utf8_aligned`, matches starting or ending inside a UTF-8 sequence are
+    /// skipped. Whether a match ending at the end of the buffered data does is only known
+    /// once the next byte is read, or at `eof`.
+    fn find_in_window(&self, eof: bool) -> WindowMatch {
+        let mut from = self.buffer_pos;
+        loop {
+            let Some(i) = self.search_in(&self.buffer[from..self.buffer_fill_len]) else {
+                return WindowMatch::None;
+            };
+            let pos = from + i;
+            if !self.utf8_aligned {
+                return WindowMatch::Found(pos);
+            }
+
+            let end = pos + self.needle.len();
+            let is_boundary = |i: usize| !is_utf8_continuation(self.buffer[i]);
+            if is_boundary(pos) {
+                if end < self.buffer_fill_len && is_boundary(end)
+                    || end == self.buffer_fill_len && eof
+                {
+                    return WindowMatch::Found(pos);
+                }
+                if end == self.buffer_fill_len {
+                    return WindowMatch::Undecided(pos);
+                }
+            }
+            from = pos + 1;
+        }
+    }
+
+    /// Reports the match at buffer index `pos`, returning its absolute offset
+    fn report_match(&mut self, pos: usize) -> u64 {
+        let match_pos = self.haystack_pos + pos as u64;
+        self.checksum_until(match_pos);
+        self.buffer_pos = pos + 1;
+        match_pos
+    }
+
+    /// Finds the next match, the iterator and `positions_u64` both build on this
+    fn next_u64(&mut self) -> Option<io::Result<u64>> {
+        if self.needle.is_empty() {
+            return self.next_empty_match();
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
         loop {
             if self.buffer_pos >= self.buffer_fill_len {
                 // Buffer is exhausted, try to read more data.
-                self.haystack_pos += self.buffer_pos;
+                self.checksum_until(self.bytes_consumed_u64());
+                self.haystack_pos += self.buffer_pos as u64;
                 self.buffer_fill_len = 0;
                 self.buffer_pos = 0;
-                match self.haystack.read(&mut self.buffer) {
+                match self.read_at(0) {
                     Ok(0) => return None,
-                    Ok(n) => {
-                        self.buffer_fill_len = n;
-                        // If needle is longer than what we could read, no match is possible
-                        if self.haystack_pos == 0 && n < self.needle.len() {
-                            return None;
-                        }
-                    }
+                    // A short read is fine, the window below asks for more data
+                    Ok(n) => self.buffer_fill_len = n,
                     Err(e) => return Some(Err(e)),
                 }
             }
 
-            let search_area = &self.buffer[self.buffer_pos..self.buffer_fill_len];
-            let found = match self.algo {
-                Algorithm::Naive => naive_search(search_area, &self.needle),
-                Algorithm::Bmh => bmh_search(search_area, &self.needle),
-                Algorithm::Kmp => kmp_search(search_area, &self.needle),
-                #[cfg(target_arch = "x86_64")]
-                Algorithm::SimdX8664 => simd_search_x86_64(search_area, &self.needle),
-                Algorithm::Simd => simd_search(search_area, &self.needle),
-            };
-
-            if let Some(i) = found {
-                let match_pos = self.buffer_pos + i;
-                self.buffer_pos = match_pos + 1;
-                return Some(Ok(self.haystack_pos + match_pos));
-            }
-
-            // No match found
-            if self.buffer_pos + self.needle.len() > self.buffer_fill_len {
-                // Need more data
-                if self.buffer_fill_len >= self.buffer.len() {
-                    // Buffer full, copy tail to start
+            match self.find_in_window(false) {
+                WindowMatch::Found(pos) => return Some(Ok(self.report_match(pos))),
+                // Keep the match buffered until the byte after it has been read
+                WindowMatch::Undecided(pos) => self.buffer_pos = pos,
+                WindowMatch::None => {
+                    // No match found, only the last needle.len() - 1 bytes can still start one
                     let tail_len = self.needle.len() - 1;
-                    self.buffer
-                        .copy_within(self.buffer_fill_len - tail_len..self.buffer_fill_len, 0);
-                    self.buffer_fill_len = tail_len;
-                    self.buffer_pos = 0;
-                    self.haystack_pos += self.buffer.len() - tail_len;
+                    self.buffer_pos = self
+                        .buffer_pos
+                        .max(self.buffer_fill_len.saturating_sub(tail_len));
                 }
-                match self.haystack.read(&mut self.buffer[self.buffer_fill_len..]) {
-                    Ok(0) => return None,
-                    Ok(n) => self.buffer_fill_len += n,
-                    Err(e) => return Some(Err(e)),
+            }
+
+            // Need more data
+            if self.buffer_fill_len >= self.buffer.len() {
+                // Buffer full, copy the unsearched tail (at most needle.len() - 1 bytes) to start
+                self.checksum_until(self.bytes_consumed_u64());
+                self.buffer
+                    .copy_within(self.buffer_pos..self.buffer_fill_len, 0);
+                self.buffer_fill_len -= self.buffer_pos;
+                self.haystack_pos += self.buffer_pos as u64;
+                self.buffer_pos = 0;
+            }
+            match self.read_at(self.buffer_fill_len) {
+                Ok(0) => {
+                    // The end of the stream is a char boundary, decide any pending match
+                    if self.utf8_aligned {
+                        if let WindowMatch::Found(pos) = self.find_in_window(true) {
+                            return Some(Ok(self.report_match(pos)));
+                        }
+                    }
+                    // Nothing left to read, the remaining tail has been searched
+                    self.buffer_pos = self.buffer_fill_len;
+                    self.checksum_until(self.bytes_consumed_u64());
+                    return None;
                 }
-            } else {
-                // Advance the window
-                let tail_len = self.needle.len() - 1;
-                let advance = (self.buffer_fill_len - self.buffer_pos) - tail_len;
-                self.haystack_pos += advance;
-                self.buffer_pos += advance;
+                Ok(n) => self.buffer_fill_len += n,
+                Err(e) => return Some(Err(e)),
             }
         }
     }
 }
+
+impl<R: Read + Seek> Finder<R> {
+    /// Creates a new Finder over a seekable reader, see `find_last_seek`
+    ///
+    /// Behaves like `Finder::new`, seekable readers such as `File` additionally support
+    /// searching backwards from the end of the stream.
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needle` - Bytes to search for
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn new_seek(
+        haystack: R,
+        needle: Vec<u8>,
+        algo: Option<Algorithm>,
+    ) -> Result<Self, FinderError> {
+        Self::new(haystack, needle, algo)
+    }
+
+    /// Finds the last match in the stream by reading it backwards from the end
+    ///
+    /// Seeks to the end, then reads buffer-sized chunks towards the start, each extended
+    /// by the first `needle.len() - 1` bytes of the chunk after it so matches spanning
+    /// chunks are found. Only the chunks after the last match are read, which makes this
+    /// a reverse search for files too large to map. Offsets are relative to the start of
+    /// the stream, whatever the reader position was before.
+    ///
+    /// # Returns
+    /// The absolute position of the last match, or None if the needle does not occur
+    ///
+    /// # Errors
+    /// Returns the first IO error raised while seeking or reading
+    pub fn find_last_seek(mut self) -> io::Result<Option<u64>> {
+        let len = self.haystack.seek(SeekFrom::End(0))?;
+        let m = self.needle.len();
+        if m == 0 {
+            // Only reachable with `EmptyNeedlePolicy::MatchAll`, the end itself matches
+            return Ok(Some(len));
+        }
+
+        let chunk = self.requested_buffer_size as u64;
+        let mut end = len;
+        while end > 0 {
+            let start = end.saturating_sub(chunk);
+            // The chunk plus the head of the next one, which fits the buffer
+            let window_len = ((len.min(end + m as u64 - 1)) - start) as usize;
+            self.haystack.seek(SeekFrom::Start(start))?;
+            self.haystack.read_exact(&mut self.buffer[..window_len])?;
+
+            let window = &self.buffer[..window_len];
+            let mut last = None;
+            let mut from = 0;
+            while let Some(i) = self.search_in(&window[from..]) {
+                last = Some(from + i);
+                from += i + 1;
+            }
+            if let Some(i) = last {
+                return Ok(Some(start + i as u64));
+            }
+            end = start;
+        }
+        Ok(None)
+    }
+}
+
+/// Reader over a byte range of a file using positioned reads
+#[cfg(any(unix, windows))]
+struct FileRangeReader<'a> {
+    file: &'a File,
+    pos: u64,
+    end: u64,
+}
+
+#[cfg(any(unix, windows))]
+impl Read for FileRangeReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = usize::try_from(self.end - self.pos).map_or(buf.len(), |n| n.min(buf.len()));
+        let buf = &mut buf[..len];
+        #[cfg(unix)]
+        let n = std::os::unix::fs::FileExt::read_at(self.file, buf, self.pos)?;
+        #[cfg(windows)]
+        let n = std::os::windows::fs::FileExt::seek_read(self.file, buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+/// Searches a byte range of an open file using positioned reads
+///
+/// The range is read in buffer-sized chunks with `FileExt::read_at` on Unix, which
+/// leaves the file cursor untouched, and `FileExt::seek_read` on Windows, which moves
+/// it. Nothing is mapped, so one file handle can serve many such searches. Only
+/// matches lying entirely within the range are found. A range reaching past the end
+/// of the file ends there.
+///
+/// # Arguments
+/// * `file` - The open file to search in
+/// * `range` - Byte offsets of the file to search
+/// * `needle` - Bytes to search for
+/// * `algo` - Search algorithm to use
+///
+/// # Returns
+/// Positions of all matches, relative to the start of the file
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the needle is empty or longer than
+/// `MAX_NEEDLE_LEN`, or the first IO error raised while reading
+#[cfg(any(unix, windows))]
+pub fn find_in_file_range(
+    file: &File,
+    range: Range<u64>,
+    needle: &[u8],
+    algo: Algorithm,
+) -> io::Result<Vec<usize>> {
+    let reader = FileRangeReader {
+        file,
+        pos: range.start,
+        end: range.end.max(range.start),
+    };
+    let mut finder = Finder::new(reader, needle.to_vec(), Some(algo))?;
+    // Offsets count from the start of the file, not of the range
+    finder.haystack_pos = range.start;
+    finder.collect_all()
+}
+
+/// Iterator implementation that yields positions of matches in the stream
+/// Returns `io::Result<usize>` indicating the position of each match or potential IO errors
+impl<R: Read> Iterator for Finder<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_u64().map(|pos| {
+            usize::try_from(pos?).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "match offset does not fit in usize, use Finder::positions_u64",
+                )
+            })
+        })
+    }
+}
+
+/// A `Finder` that can look at its next match without consuming it
+///
+/// Created by `Finder::peekable`.
+pub struct PeekableFinder<R: Read> {
+    finder: Finder<R>,
+    /// The next item, once peeked, `Some(None)` when the finder is done
+    peeked: Option<Option<io::Result<usize>>>,
+}
+
+impl<R: Read> PeekableFinder<R> {
+    /// Returns the next match without consuming it
+    ///
+    /// Peeking repeatedly returns the same item, `next` then yields it. An IO error is
+    /// returned by reference, so it is still yielded by `next`.
+    pub fn peek(&mut self) -> Option<&io::Result<usize>> {
+        let finder = &mut self.finder;
+        self.peeked.get_or_insert_with(|| finder.next()).as_ref()
+    }
+
+    /// Returns the position of the next match without consuming it
+    ///
+    /// None if there is no further match or the next item is an IO error, use `peek`
+    /// to tell these apart.
+    pub fn peek_offset(&mut self) -> Option<usize> {
+        self.peek()?.as_ref().ok().copied()
+    }
+}
+
+impl<R: Read> Iterator for PeekableFinder<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.finder.next(),
+        }
+    }
+}
@@ -6,6 +6,46 @@ pub fn decode<T: AsRef<[u8]>>(data: T) -> Result<Vec<u8>, FromHexError> {
     FromHex::try_from_hex(data)
 }
 
+/// Decodes a hex encoded needle, as given on the command line with `--hex`
+///
+/// Like `decode`, but an input decoding to zero bytes is rejected here with a clear
+/// message rather than later by the finder constructors.
+///
+/// # Errors
+/// Returns `FromHexError` if input contains invalid hex characters, has odd length or
+/// is empty
+pub fn decode_needle<T: AsRef<[u8]>>(data: T) -> Result<Vec<u8>, FromHexError> {
+    let needle = decode(data)?;
+    if needle.is_empty() {
+        return Err(FromHexError::EmptyNeedle);
+    }
+    Ok(needle)
+}
+
+/// Decodes a hex pattern where `??` stands for any byte
+///
+/// For example `de??beef` decodes to `[Some(0xde), None, Some(0xbe), Some(0xef)]`,
+/// ready for `wildcard_search`.
+///
+/// # Errors
+/// Returns `FromHexError` if input contains invalid hex characters, a lone `?` or has odd length
+pub fn decode_wildcard<T: AsRef<[u8]>>(data: T) -> Result<Vec<Option<u8>>, FromHexError> {
+    let hex = data.as_ref();
+    if hex.len() % 2 != 0 {
+        return Err(FromHexError::OddLength {
+            index: hex.len() - 1,
+        });
+    }
+
+    hex.chunks(2)
+        .enumerate()
+        .map(|(i, pair)| match pair {
+            b"??" => Ok(None),
+            _ => Ok(Some(val(pair[0], 2 * i)? << 4 | val(pair[1], 2 * i + 1)?)),
+        })
+        .collect()
+}
+
 /// Converts a hex character to its numeric value
 fn val(c: u8, idx: usize) -> Result<u8, FromHexError> {
     match c {
@@ -27,9 +67,13 @@ pub enum FromHexError {
         c: char,
         index: usize,
     },
-    /// Input string has an odd length
-    OddLength,
+    /// Input string has an odd length, `index` is the position of the unpaired digit
+    OddLength {
+        index: usize,
+    },
     InvalidStringLength,
+    /// Input decoded to zero bytes, see `decode_needle`
+    EmptyNeedle,
 }
 
 impl std::error::Error for FromHexError {}
@@ -40,8 +84,15 @@ impl std::fmt::Display for FromHexError {
             FromHexError::InvalidHexCharacter { c, index } => {
                 write!(f, "Invalid character {:?} at position {}", c, index)
             }
-            FromHexError::OddLength => write!(f, "Odd number of digits"),
+            FromHexError::OddLength { index } => {
+                write!(
+                    f,
+                    "Odd number of digits, unpaired digit at position {}",
+                    index
+                )
+            }
             FromHexError::InvalidStringLength => write!(f, "Invalid string length"),
+            FromHexError::EmptyNeedle => write!(f, "hex needle decoded to zero bytes"),
         }
     }
 }
@@ -58,7 +109,9 @@ impl FromHex for Vec<u8> {
     fn try_from_hex<T: AsRef<[u8]>>(hex: T) -> Result<Self, Self::Error> {
         let hex = hex.as_ref();
         if hex.len() % 2 != 0 {
-            return Err(FromHexError::OddLength);
+            return Err(FromHexError::OddLength {
+                index: hex.len() - 1,
+            });
         }
 
         hex.chunks(2)
@@ -67,3 +120,50 @@ impl FromHex for Vec<u8> {
             .collect()
     }
 }
+
+/// Incremental hex decoder for input that arrives in chunks
+///
+/// Chunks can be split anywhere, a dangling digit is carried over to the next chunk.
+/// Character errors report their index within the whole input, not the chunk.
+#[derive(Debug, Default)]
+pub struct HexDecoder {
+    decoded: Vec<u8>,
+    pending: Option<u8>,
+    index: usize,
+}
+
+impl HexDecoder {
+    /// Creates an empty decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the next chunk of hex input
+    ///
+    /// # Errors
+    /// Returns `FromHexError::InvalidHexCharacter` if the chunk contains a non-hex character
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), FromHexError> {
+        for &c in chunk {
+            let nibble = val(c, self.index)?;
+            self.index += 1;
+            match self.pending.take() {
+                Some(high) => self.decoded.push(high << 4 | nibble),
+                None => self.pending = Some(nibble),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the decoded bytes
+    ///
+    /// # Errors
+    /// Returns `FromHexError::OddLength` if a digit is still waiting for its pair
+    pub fn finish(self) -> Result<Vec<u8>, FromHexError> {
+        match self.pending {
+            Some(_) => Err(FromHexError::OddLength {
+                index: self.index - 1,
+            }),
+            None => Ok(self.decoded),
+        }
+    }
+}
@@ -2,25 +2,105 @@
 #![feature(const_cmp)]
 #![feature(const_trait_impl)]
 #![feature(core_intrinsics)]
+#![allow(internal_features)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! A fast, streaming needle-in-haystack searcher using various algorithms including SIMD
 //!
 //! This crate provides utilities for searching byte patterns in streams efficiently,
 //! supporting multiple search algorithms including naive, Boyer-Moore-Horspool, KMP, and SIMD.
 //!
-//! Three implementations are provided:
+//! The following implementations are provided:
 //! - `Finder`: Streaming implementation for any Read source
 //! - `MmapFinder`: Zero-copy implementation for memory-mapped files
+//! - `MultiFinder`: Streaming implementation checking several needles per pass
+//! - `SliceFinder`: `MmapFinder` counterpart for bytes already in memory
+//! - `ContextFinder`: Streaming implementation reporting the bytes around each match
+//!
+//! These need the default `std` feature. Without it the crate is `no_std` and only
+//! provides the search functions and `lines`, which need nothing beyond `alloc`.
+
+#[macro_use]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+mod context_finder;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
 mod finder;
+#[cfg(feature = "std")]
 pub mod hex;
+pub mod lines;
+#[cfg(feature = "std")]
 mod mmap_finder;
+#[cfg(feature = "std")]
+mod multi_finder;
 mod search;
+#[cfg(feature = "std")]
+mod slice_finder;
 
-pub use finder::{Finder, FinderError, FinderTrait, DEFAULT_BUF_SIZE};
-pub use mmap_finder::{find_in_file, find_in_mmap, MmapFinder, MmapFinderError};
+#[cfg(feature = "std")]
+pub use context_finder::{ContextFinder, ContextMatch};
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub use finder::find_in_file_range;
+#[cfg(feature = "serde")]
+pub use finder::Match;
+#[cfg(feature = "std")]
+pub use finder::{
+    EmptyNeedlePolicy, Finder, FinderError, FinderTrait, FromEnvError, PeekableFinder,
+    DEFAULT_BUF_SIZE, MAX_NEEDLE_LEN,
+};
+#[cfg(feature = "std")]
+pub use mmap_finder::{
+    count_in_file, count_in_file_with, find_in_file, find_in_file_parallel, find_in_file_with,
+    find_in_mmap, find_nth_in_file, MmapFinder, MmapFinderError, MmapFinderIter, PROGRESS_INTERVAL,
+};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use mmap_finder::{Codec, MAX_DECOMPRESSED_LEN};
+#[cfg(feature = "std")]
+pub use multi_finder::MultiFinder;
 #[cfg(target_arch = "x86_64")]
 pub use search::simd_search_x86_64;
-pub use search::{bmh_search, kmp_search, naive_search, simd_search, Algorithm as SearchAlgo};
+pub use search::{
+    bmh_find_all, bmh_search, differential_check, find_all_in_slice, kmp_prefix_table, kmp_search,
+    kmp_search_with_table, naive_search, naive_search_ascii_ci, pattern_search, shift_and_search,
+    shift_and_search_ascii_ci, simd_find_byte, simd_find_byte_iter, simd_search, simd_search_lanes,
+    simd_search_tuned, wildcard_search, Algorithm as SearchAlgo, ParseAlgorithmError, Pattern,
+    PatternError, SearchFn, SimdFindByteIter, PATTERN_MAX_LEN,
+};
+#[cfg(feature = "std")]
+pub use slice_finder::SliceFinder;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
+
+/// Exercises the search functions in a `no_std` build, run with
+/// `cargo test --lib --no-default-features`
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_search_functions_without_std() {
+        let haystack = b"abcabcabd";
+        let needle = b"abd";
+        assert_eq!(naive_search(haystack, needle), Some(6));
+        assert_eq!(bmh_search(haystack, needle), Some(6));
+        assert_eq!(kmp_search(haystack, needle), Some(6));
+        assert_eq!(shift_and_search(haystack, needle), Some(6));
+        assert_eq!(simd_search(haystack, needle), Some(6));
+        assert_eq!(bmh_find_all(haystack, b"abc"), vec![0, 3]);
+        for &algo in SearchAlgo::ALL {
+            assert_eq!(find_all_in_slice(haystack, b"ab", algo), vec![0, 3, 6]);
+        }
+        assert_eq!(
+            simd_find_byte_iter(haystack, b'c').collect::<Vec<_>>(),
+            vec![2, 5]
+        );
+        assert_eq!("kmp".parse::<SearchAlgo>(), Ok(SearchAlgo::Kmp));
+    }
+}
@@ -0,0 +1,20 @@
+use crate::search::simd_find_byte_iter;
+
+/// Converts a byte offset into a 1-based `(line, column)` pair
+///
+/// Lines are split on `\n`, so CRLF files count the same as LF files and the `\r`
+/// is simply the last column of its line. Columns count bytes, not characters.
+/// Offsets past the end of `data` are reported relative to the last line.
+///
+/// # Arguments
+/// * `data` - The bytes the offset points into
+/// * `offset` - Byte offset, e.g. a match position
+///
+/// # Returns
+/// Tuple of the line and column of the byte at `offset`
+pub fn offset_to_line_col(data: &[u8], offset: usize) -> (usize, usize) {
+    let before = &data[..offset.min(data.len())];
+    let (newlines, line_start) =
+        simd_find_byte_iter(before, b'\n').fold((0, 0), |(count, _), pos| (count + 1, pos + 1));
+    (newlines + 1, offset - line_start + 1)
+}
@@ -1,17 +1,92 @@
+use std::collections::VecDeque;
 use std::fs::File;
+use std::ops::ControlFlow;
 use std::path::Path;
 
-use memmap2::Mmap;
+use memmap2::{Mmap, MmapOptions};
+use rayon::prelude::*;
 
-#[cfg(target_arch = "x86_64")]
-use crate::search::simd_search_x86_64;
-use crate::search::{bmh_search, kmp_search, naive_search, simd_search, Algorithm};
+use crate::finder::{Finder, FinderTrait, DEFAULT_BUF_SIZE};
+use crate::hex::{self, FromHexError};
+use crate::search::{search, search_ascii_ci, simd_find_byte, Algorithm, PreparedSearch};
+
+/// Number of bytes scanned between two progress callbacks (64MB)
+pub const PROGRESS_INTERVAL: usize = 64 * 1024 * 1024;
+
+/// Smallest part of a file searched by one thread in `find_all_parallel` (1MB)
+///
+/// Keeps small files from being split into pieces that cost more to schedule than to
+/// search.
+const PARALLEL_MIN_CHUNK: usize = 1024 * 1024;
+
+/// Largest decompressed size `MmapFinder::new_compressed` accepts (16GB)
+///
+/// The data goes to a temporary file rather than memory, the limit keeps a small
+/// compressed bomb from filling the disk.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub const MAX_DECOMPRESSED_LEN: u64 = 16 * 1024 * 1024 * 1024;
 
 /// Errors that can occur when working with memory-mapped files
 #[derive(Debug)]
 pub enum MmapFinderError {
     Io(std::io::Error),
     EmptyNeedle,
+    Hex(FromHexError),
+    /// The path is a directory, FIFO or other file that cannot be mapped
+    NotARegularFile,
+}
+
+/// Compression formats `MmapFinder::new_compressed` can decompress, one feature each
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// gzip, needs the `gzip` feature
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard, needs the `zstd` feature
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Opens `path` for mapping, rejecting anything but regular files
+///
+/// The metadata is checked before opening, which would block on a FIFO.
+fn open_regular_file(path: &Path) -> Result<File, MmapFinderError> {
+    let metadata = std::fs::metadata(path).map_err(MmapFinderError::Io)?;
+    if !metadata.is_file() {
+        return Err(MmapFinderError::NotARegularFile);
+    }
+    File::open(path).map_err(MmapFinderError::Io)
+}
+
+/// Writes everything `decoder` yields to an unnamed temporary file
+///
+/// Fails with `io::ErrorKind::InvalidData` once more than `limit` bytes come out.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn decompress_to_file<R: std::io::Read>(
+    decoder: R,
+    limit: u64,
+) -> std::io::Result<File> {
+    let mut file = tempfile::tempfile()?;
+    let written = std::io::copy(&mut decoder.take(limit.saturating_add(1)), &mut file)?;
+    if written > limit {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "decompressed data exceeds the size limit",
+        ));
+    }
+    Ok(file)
+}
+
+/// Rejects empty needles, the whole file is mapped so there is no buffer limit
+///
+/// The mapped counterpart of `validate_needle`, kept separate so every rule maps to an
+/// `MmapFinderError` of its own.
+fn check_needle(needle: &[u8]) -> Result<(), MmapFinderError> {
+    if needle.is_empty() {
+        return Err(MmapFinderError::EmptyNeedle);
+    }
+    Ok(())
 }
 
 /// Zero-copy finder for memory-mapped files
@@ -19,6 +94,13 @@ pub enum MmapFinderError {
 /// This provides APIs for searching in memory-mapped files without copying data.
 /// Memory-mapped files allow efficient access to large files by treating them as if
 /// they are in memory, with the OS handling paging transparently.
+///
+/// # Thread safety
+/// `MmapFinder` is `Send + Sync`: the mapping is read-only, the needle is only replaced
+/// through `set_needle`, which takes `&mut self`, and every search method takes `&self`.
+/// One finder can be shared across threads (e.g. rayon workers) that all call
+/// `find_all`, `find_first` and friends concurrently. Any interior mutability added later, such
+/// as cached search tables, must keep this contract.
 pub struct MmapFinder {
     mmap: Mmap,
     needle: Vec<u8>,
@@ -33,17 +115,115 @@ impl MmapFinder {
     ///
     /// # Returns
     /// Result containing the MmapFinder or an error
+    ///
+    /// # Errors
+    /// Returns `MmapFinderError::NotARegularFile` for directories, FIFOs and other files
+    /// that cannot be mapped, checked before the file is opened
     pub fn new<P: AsRef<Path>>(path: P, needle: Vec<u8>) -> Result<Self, MmapFinderError> {
-        if needle.is_empty() {
-            return Err(MmapFinderError::EmptyNeedle);
-        }
+        check_needle(&needle)?;
 
-        let file = File::open(path).map_err(MmapFinderError::Io)?;
+        let file = open_regular_file(path.as_ref())?;
+        // SAFETY: the mapping is only ever read. As with any mmap, the file must not be
+        // truncated or modified by another process while the finder is alive.
         let mmap = unsafe { Mmap::map(&file).map_err(MmapFinderError::Io)? };
 
         Ok(Self { mmap, needle })
     }
 
+    /// Create a new MmapFinder from a file path, pre-faulting the whole mapping
+    ///
+    /// Uses `MAP_POPULATE` so the file is read in up front instead of page by page
+    /// during the scan, which helps latency-sensitive one-shot searches. Platforms
+    /// without `MAP_POPULATE` get a normal mapping.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the file to memory-map
+    /// * `needle` - Bytes to search for
+    ///
+    /// # Returns
+    /// Result containing the MmapFinder or an error
+    pub fn new_populated<P: AsRef<Path>>(
+        path: P,
+        needle: Vec<u8>,
+    ) -> Result<Self, MmapFinderError> {
+        check_needle(&needle)?;
+
+        let file = open_regular_file(path.as_ref())?;
+        // SAFETY: see `MmapFinder::new`
+        let mmap = unsafe {
+            MmapOptions::new()
+                .populate()
+                .map(&file)
+                .map_err(MmapFinderError::Io)?
+        };
+
+        Ok(Self { mmap, needle })
+    }
+
+    /// Create a new MmapFinder from a file path and a hex encoded needle
+    ///
+    /// # Arguments
+    /// * `path` - Path to the file to memory-map
+    /// * `hex_needle` - Hex encoded bytes to search for, e.g. `"deadbeef"`
+    ///
+    /// # Returns
+    /// Result containing the MmapFinder or an error
+    ///
+    /// # Errors
+    /// Returns `MmapFinderError::Hex` if the needle is not valid hex
+    pub fn new_hex<P: AsRef<Path>>(path: P, hex_needle: &str) -> Result<Self, MmapFinderError> {
+        let needle = hex::decode(hex_needle).map_err(MmapFinderError::Hex)?;
+        Self::new(path, needle)
+    }
+
+    /// Create a new MmapFinder over the decompressed contents of a compressed file
+    ///
+    /// The file is decompressed once into an unnamed temporary file, which is then mapped
+    /// and searched zero-copy like any other file. This suits archives searched
+    /// repeatedly, e.g. with `set_needle`. Offsets are relative to the decompressed data.
+    /// Decompression streams through a small buffer, so memory use does not grow with the
+    /// decompressed size, which is limited to `MAX_DECOMPRESSED_LEN`.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the compressed file
+    /// * `needle` - Bytes to search for
+    /// * `codec` - Compression format of the file
+    ///
+    /// # Returns
+    /// Result containing the MmapFinder or an error
+    ///
+    /// # Errors
+    /// Returns `MmapFinderError::Io` if the file cannot be read, is not valid `codec`
+    /// data or decompresses to more than `MAX_DECOMPRESSED_LEN` bytes,
+    /// `MmapFinderError::EmptyNeedle` if the needle is empty and
+    /// `MmapFinderError::NotARegularFile` for directories, FIFOs and other special files
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn new_compressed<P: AsRef<Path>>(
+        path: P,
+        needle: Vec<u8>,
+        codec: Codec,
+    ) -> Result<Self, MmapFinderError> {
+        check_needle(&needle)?;
+
+        let file = std::io::BufReader::new(open_regular_file(path.as_ref())?);
+        let decompressed = match codec {
+            #[cfg(feature = "gzip")]
+            Codec::Gzip => decompress_to_file(
+                flate2::bufread::MultiGzDecoder::new(file),
+                MAX_DECOMPRESSED_LEN,
+            ),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => zstd::Decoder::with_buffer(file)
+                .and_then(|decoder| decompress_to_file(decoder, MAX_DECOMPRESSED_LEN)),
+        }
+        .map_err(MmapFinderError::Io)?;
+
+        // SAFETY: the temporary file is unnamed, so no other process can modify it
+        let mmap = unsafe { Mmap::map(&decompressed).map_err(MmapFinderError::Io)? };
+
+        Ok(Self { mmap, needle })
+    }
+
     /// Create a new MmapFinder from an existing Mmap
     ///
     /// # Arguments
@@ -53,9 +233,7 @@ impl MmapFinder {
     /// # Returns
     /// Result containing the MmapFinder or an error
     pub fn from_mmap(mmap: Mmap, needle: Vec<u8>) -> Result<Self, MmapFinderError> {
-        if needle.is_empty() {
-            return Err(MmapFinderError::EmptyNeedle);
-        }
+        check_needle(&needle)?;
 
         Ok(Self { mmap, needle })
     }
@@ -68,11 +246,179 @@ impl MmapFinder {
     /// # Returns
     /// Iterator yielding positions of matches
     pub fn find_all(&self, algo: Algorithm) -> MmapFinderIter<'_> {
-        MmapFinderIter {
-            haystack: &self.mmap,
-            needle: &self.needle,
-            algo,
-            pos: 0,
+        self.find_all_with(algo, true)
+    }
+
+    /// Find all occurrences of the needle, choosing whether matches may overlap
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `overlapping` - If false, the search resumes after the end of each match
+    ///
+    /// # Returns
+    /// Iterator yielding positions of matches
+    pub fn find_all_with(&self, algo: Algorithm, overlapping: bool) -> MmapFinderIter<'_> {
+        MmapFinderIter::new(&self.mmap, &self.needle, algo, overlapping)
+    }
+
+    /// Find all occurrences of the needle, yielding the matched bytes instead of offsets
+    ///
+    /// For a literal needle every slice equals the needle; the slices become meaningful
+    /// once searches can match variable-length spans.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Iterator yielding the mapped bytes of each, possibly overlapping, match
+    pub fn find_all_slices(&self, algo: Algorithm) -> impl Iterator<Item = &[u8]> + '_ {
+        let len = self.needle.len();
+        self.find_all(algo)
+            .map(move |pos| &self.mmap[pos..pos + len])
+    }
+
+    /// Count all occurrences of the needle, overlapping matches are counted individually
+    ///
+    /// Like `find_all`, this counts overlapping matches: `aa` occurs 3 times in `aaaa`.
+    /// Use `count_with` to count non-overlapping matches instead.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Number of matches
+    pub fn count(&self, algo: Algorithm) -> usize {
+        self.count_with(algo, true)
+    }
+
+    /// Count all occurrences of the needle, choosing whether matches may overlap
+    ///
+    /// Counts the matches `find_all_with` yields: `aa` occurs 3 times in `aaaa` when
+    /// overlapping and 2 times when not.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `overlapping` - If false, counting resumes after the end of each match
+    ///
+    /// # Returns
+    /// Number of matches
+    pub fn count_with(&self, algo: Algorithm, overlapping: bool) -> usize {
+        self.find_all_with(algo, overlapping).count()
+    }
+
+    /// Number of matches `find_all` will yield, for sizing progress reports up front
+    ///
+    /// Unlike a streaming `Finder`, which cannot know the total before reading the whole
+    /// stream, this is the exact count for the mapped file. It is computed with a full
+    /// scan, so it costs as much as the search itself.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Exact number of matches
+    pub fn len_hint(&self, algo: Algorithm) -> usize {
+        self.count(algo)
+    }
+
+    /// Find all occurrences of the needle using every thread of the rayon pool
+    ///
+    /// The file is split into one chunk per thread, of at least 1MB each, and the
+    /// chunks are searched in parallel. Each chunk is searched together with the first
+    /// `needle.len() - 1` bytes of the next, so matches across chunk boundaries are
+    /// found exactly once. The result equals `find_all`, positions are in order.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Vector of positions of all, possibly overlapping, matches
+    pub fn find_all_parallel(&self, algo: Algorithm) -> Vec<usize> {
+        let chunk_len = (self.mmap.len() / rayon::current_num_threads()).max(PARALLEL_MIN_CHUNK);
+        self.find_all_in_chunks(algo, chunk_len)
+    }
+
+    /// Searches chunks of `chunk_len` bytes in parallel, see `find_all_parallel`
+    pub(crate) fn find_all_in_chunks(&self, algo: Algorithm, chunk_len: usize) -> Vec<usize> {
+        let haystack: &[u8] = &self.mmap;
+        let prepared = PreparedSearch::new(algo, &self.needle);
+        (0..haystack.len().div_ceil(chunk_len))
+            .into_par_iter()
+            .map(|chunk| {
+                let mut pos = chunk * chunk_len;
+                // Matches must start in the chunk, but may extend into the next one
+                let chunk_end = (pos + chunk_len).min(haystack.len());
+                let window_end = (chunk_end + self.needle.len() - 1).min(haystack.len());
+                let mut positions = Vec::new();
+                while let Some(i) = prepared.search(&haystack[pos..window_end], &self.needle) {
+                    positions.push(pos + i);
+                    pos += i + 1;
+                }
+                positions
+            })
+            .collect::<Vec<_>>()
+            .concat()
+    }
+
+    /// Find all occurrences of the needle, reporting progress along the way
+    ///
+    /// The file is scanned in windows of `PROGRESS_INTERVAL` bytes and `progress` is called
+    /// with the absolute offset reached after each window. The last call always reports
+    /// the file length, so it fires at least once even for small files.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `progress` - Callback receiving the number of bytes scanned so far
+    ///
+    /// # Returns
+    /// Vector of positions of all matches
+    pub fn find_all_with_progress(
+        &self,
+        algo: Algorithm,
+        mut progress: impl FnMut(usize),
+    ) -> Vec<usize> {
+        let haystack: &[u8] = &self.mmap;
+        let mut positions = Vec::new();
+        let mut pos = 0;
+        let mut next_report = PROGRESS_INTERVAL.min(haystack.len());
+        let prepared = PreparedSearch::new(algo, &self.needle);
+
+        loop {
+            // Matches must start before the report offset, but may extend past it
+            let window_end = (next_report + self.needle.len() - 1).min(haystack.len());
+            match prepared.search(&haystack[pos..window_end], &self.needle) {
+                Some(i) => {
+                    positions.push(pos + i);
+                    pos += i + 1;
+                }
+                None => {
+                    pos = next_report;
+                    progress(pos);
+                    if pos >= haystack.len() {
+                        return positions;
+                    }
+                    next_report = (next_report + PROGRESS_INTERVAL).min(haystack.len());
+                }
+            }
+        }
+    }
+
+    /// Call `f` with the position of every match, overlapping matches included
+    ///
+    /// Nothing is allocated. The scan stops as soon as `f` returns `ControlFlow::Break`.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `f` - Callback receiving each match position in increasing order
+    pub fn for_each_match(&self, algo: Algorithm, mut f: impl FnMut(usize) -> ControlFlow<()>) {
+        let haystack: &[u8] = &self.mmap;
+        let prepared = PreparedSearch::new(algo, &self.needle);
+        let mut pos = 0;
+        while let Some(i) = prepared.search(&haystack[pos..], &self.needle) {
+            if f(pos + i).is_break() {
+                return;
+            }
+            pos += i + 1;
         }
     }
 
@@ -84,15 +430,88 @@ impl MmapFinder {
     /// # Returns
     /// Option containing the position of the first match, or None if not found
     pub fn find_first(&self, algo: Algorithm) -> Option<usize> {
-        let search_area = &self.mmap;
-        match algo {
-            Algorithm::Naive => naive_search(search_area, &self.needle),
-            Algorithm::Bmh => bmh_search(search_area, &self.needle),
-            Algorithm::Kmp => kmp_search(search_area, &self.needle),
-            #[cfg(target_arch = "x86_64")]
-            Algorithm::SimdX8664 => simd_search_x86_64(search_area, &self.needle),
-            Algorithm::Simd => simd_search(search_area, &self.needle),
-        }
+        search(algo, &self.mmap, &self.needle)
+    }
+
+    /// Find all occurrences of the needle, ignoring ASCII case
+    ///
+    /// ASCII letters match in either case, all other bytes must be equal. The mapping is
+    /// never modified, folding happens while comparing. `Algorithm::Naive` uses
+    /// `naive_search_ascii_ci`, every other algorithm `shift_and_search_ascii_ci`, which
+    /// falls back to the naive variant for needles longer than 64 bytes.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Iterator yielding positions of, possibly overlapping, matches
+    pub fn find_all_ci(&self, algo: Algorithm) -> impl Iterator<Item = usize> + '_ {
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            let i = search_ascii_ci(algo, self.mmap.get(pos..)?, &self.needle)?;
+            let found = pos + i;
+            pos = found + 1;
+            Some(found)
+        })
+    }
+
+    /// Find the first occurrence of the needle, ignoring ASCII case, see `find_all_ci`
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Option containing the position of the first match, or None if not found
+    pub fn find_first_ci(&self, algo: Algorithm) -> Option<usize> {
+        search_ascii_ci(algo, &self.mmap, &self.needle)
+    }
+
+    /// Find the first occurrence of the needle at or after `start`
+    ///
+    /// Useful to resume a scan, e.g. `find_from(last + 1, algo)`.
+    ///
+    /// # Arguments
+    /// * `start` - Absolute offset to start searching at, clamped to the file length
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Option containing the absolute position of the match, or None if not found
+    pub fn find_from(&self, start: usize, algo: Algorithm) -> Option<usize> {
+        let start = start.min(self.mmap.len());
+        search(algo, &self.mmap[start..], &self.needle).map(|i| start + i)
+    }
+
+    /// Find the last occurrence of the needle
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Option containing the position of the last match, or None if not found
+    pub fn find_last(&self, algo: Algorithm) -> Option<usize> {
+        self.find_all(algo).next_back()
+    }
+
+    /// Replaces the needle, keeping the existing mapping
+    ///
+    /// Searching one file for many needles this way maps it once instead of once per
+    /// needle. Iterators borrow the finder, so none can outlive the old needle.
+    ///
+    /// # Errors
+    /// Returns `MmapFinderError::EmptyNeedle` if the needle is empty, the previous
+    /// needle is kept then
+    pub fn set_needle(&mut self, needle: Vec<u8>) -> Result<(), MmapFinderError> {
+        check_needle(&needle)?;
+        self.needle = needle;
+        Ok(())
+    }
+
+    /// Consumes the finder, returning the mapping and the needle
+    ///
+    /// The counterpart of `from_mmap`, for reusing the mapped region elsewhere without
+    /// mapping the file again.
+    pub fn into_inner(self) -> (Mmap, Vec<u8>) {
+        (self.mmap, self.needle)
     }
 
     /// Get a reference to the underlying memory-mapped data
@@ -101,36 +520,105 @@ impl MmapFinder {
     }
 }
 
-/// Iterator for finding all occurrences in a memory-mapped file
+/// Iterator for finding all occurrences in a memory-mapped file or slice
+///
+/// Also iterates from the back: `rev()` yields exactly the forward matches in reverse
+/// order, and mixing `next` with `next_back` never reports a match twice. Without
+/// overlaps the last match depends on every earlier one, so the first `next_back`
+/// collects the remaining matches and later calls from either end take from them.
+#[derive(Clone)]
 pub struct MmapFinderIter<'a> {
     haystack: &'a [u8],
     needle: &'a [u8],
-    algo: Algorithm,
+    /// Tables of the selected algorithm, built once for all matches
+    prepared: PreparedSearch,
     pos: usize,
+    /// Matches must end at or before this offset, moved down by `next_back`
+    end: usize,
+    overlapping: bool,
+    /// Remaining non-overlapping matches, collected by the first `next_back`
+    buffered: Option<VecDeque<usize>>,
+}
+
+/// Bytes searched per step when `next_back` looks for the last overlapping match
+const REVERSE_CHUNK: usize = 4096;
+
+impl<'a> MmapFinderIter<'a> {
+    pub(crate) fn new(
+        haystack: &'a [u8],
+        needle: &'a [u8],
+        algo: Algorithm,
+        overlapping: bool,
+    ) -> Self {
+        Self {
+            haystack,
+            needle,
+            prepared: PreparedSearch::new(algo, needle),
+            pos: 0,
+            end: haystack.len(),
+            overlapping,
+            buffered: None,
+        }
+    }
+
+    fn search(&self, area: &[u8]) -> Option<usize> {
+        match self.needle {
+            // Single byte needles skip the per-algorithm dispatch, every algorithm
+            // reduces to a byte scan for them anyway
+            &[byte] => simd_find_byte(area, byte),
+            _ => self.prepared.search(area, self.needle),
+        }
+    }
+
+    /// Last match in `haystack[pos..end]`, matches may overlap
+    ///
+    /// Searches chunks of `REVERSE_CHUNK` bytes from the back with the selected
+    /// algorithm, consecutive chunks sharing `needle.len() - 1` bytes so no match is
+    /// split between them.
+    fn rfind_overlapping(&self) -> Option<usize> {
+        let m = self.needle.len();
+        let area = self.haystack.get(self.pos..self.end)?;
+        let mut chunk_end = area.len();
+        while chunk_end >= m {
+            let chunk_start = chunk_end.saturating_sub(REVERSE_CHUNK + m - 1);
+            let chunk = &area[chunk_start..chunk_end];
+            let mut last = None;
+            let mut at = 0;
+            while let Some(i) = self.search(&chunk[at..]) {
+                last = Some(at + i);
+                at += i + 1;
+            }
+            if let Some(i) = last {
+                return Some(self.pos + chunk_start + i);
+            }
+            if chunk_start == 0 {
+                break;
+            }
+            chunk_end = chunk_start + m - 1;
+        }
+        None
+    }
 }
 
 impl<'a> Iterator for MmapFinderIter<'a> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos >= self.haystack.len() {
+        if let Some(buffered) = &mut self.buffered {
+            return buffered.pop_front();
+        }
+        if self.pos >= self.end {
             return None;
         }
 
-        let search_area = &self.haystack[self.pos..];
-        let found = match self.algo {
-            Algorithm::Naive => naive_search(search_area, self.needle),
-            Algorithm::Bmh => bmh_search(search_area, self.needle),
-            Algorithm::Kmp => kmp_search(search_area, self.needle),
-            #[cfg(target_arch = "x86_64")]
-            Algorithm::SimdX8664 => simd_search_x86_64(search_area, self.needle),
-            Algorithm::Simd => simd_search(search_area, self.needle),
-        };
-
-        match found {
+        match self.search(&self.haystack[self.pos..self.end]) {
             Some(i) => {
                 let match_pos = self.pos + i;
-                self.pos = match_pos + 1;
+                self.pos = if self.overlapping {
+                    match_pos + 1
+                } else {
+                    match_pos + self.needle.len()
+                };
                 Some(match_pos)
             }
             None => None,
@@ -138,6 +626,27 @@ impl<'a> Iterator for MmapFinderIter<'a> {
     }
 }
 
+impl DoubleEndedIterator for MmapFinderIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let m = self.needle.len();
+        if m == 0 {
+            return None;
+        }
+
+        if !self.overlapping {
+            if self.buffered.is_none() {
+                self.buffered = Some(self.clone().collect());
+            }
+            return self.buffered.as_mut()?.pop_back();
+        }
+
+        let found = self.rfind_overlapping()?;
+        // Later matches from either end must start before `found`
+        self.end = found + m - 1;
+        Some(found)
+    }
+}
+
 /// Convenience function to search a memory-mapped file
 ///
 /// # Arguments
@@ -156,6 +665,120 @@ pub fn find_in_file<P: AsRef<Path>>(
     Ok(finder.find_all(algo).collect())
 }
 
+/// Convenience function to search a file with all threads, see `find_all_parallel`
+///
+/// Paths that cannot be mapped, such as FIFOs, are streamed through a `Finder` on the
+/// calling thread instead. Positions are the same either way.
+///
+/// # Arguments
+/// * `path` - Path to the file
+/// * `needle` - Bytes to search for
+/// * `algo` - Search algorithm to use
+///
+/// # Returns
+/// Vector of positions of all, possibly overlapping, matches
+pub fn find_in_file_parallel<P: AsRef<Path>>(
+    path: P,
+    needle: &[u8],
+    algo: Algorithm,
+) -> Result<Vec<usize>, MmapFinderError> {
+    match MmapFinder::new(&path, needle.to_vec()) {
+        Ok(finder) => Ok(finder.find_all_parallel(algo)),
+        Err(MmapFinderError::NotARegularFile) => {
+            let file = File::open(path).map_err(MmapFinderError::Io)?;
+            let buffer_size = DEFAULT_BUF_SIZE.max(needle.len());
+            // The needle was checked by `MmapFinder::new` and the buffer fits it
+            Finder::with_buffer_size(file, needle.to_vec(), buffer_size, Some(algo))
+                .expect("valid needle")
+                .collect_all()
+                .map_err(MmapFinderError::Io)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Convenience function to search a memory-mapped file, choosing whether matches may overlap
+///
+/// # Arguments
+/// * `path` - Path to the file
+/// * `needle` - Bytes to search for
+/// * `algo` - Search algorithm to use
+/// * `overlapping` - If false, the search resumes after the end of each match
+///
+/// # Returns
+/// Vector of positions of all matches
+pub fn find_in_file_with<P: AsRef<Path>>(
+    path: P,
+    needle: &[u8],
+    algo: Algorithm,
+    overlapping: bool,
+) -> Result<Vec<usize>, MmapFinderError> {
+    let finder = MmapFinder::new(path, needle.to_vec())?;
+    Ok(finder.find_all_with(algo, overlapping).collect())
+}
+
+/// Convenience function to count matches in a memory-mapped file
+///
+/// # Arguments
+/// * `path` - Path to the file
+/// * `needle` - Bytes to search for
+/// * `algo` - Search algorithm to use
+///
+/// # Returns
+/// Number of matches, overlapping matches are counted individually, see
+/// `count_in_file_with`
+pub fn count_in_file<P: AsRef<Path>>(
+    path: P,
+    needle: &[u8],
+    algo: Algorithm,
+) -> Result<usize, MmapFinderError> {
+    let finder = MmapFinder::new(path, needle.to_vec())?;
+    Ok(finder.count(algo))
+}
+
+/// Convenience function to count matches in a memory-mapped file, choosing whether
+/// matches may overlap
+///
+/// # Arguments
+/// * `path` - Path to the file
+/// * `needle` - Bytes to search for
+/// * `algo` - Search algorithm to use
+/// * `overlapping` - If false, counting resumes after the end of each match
+///
+/// # Returns
+/// Number of matches
+pub fn count_in_file_with<P: AsRef<Path>>(
+    path: P,
+    needle: &[u8],
+    algo: Algorithm,
+    overlapping: bool,
+) -> Result<usize, MmapFinderError> {
+    let finder = MmapFinder::new(path, needle.to_vec())?;
+    Ok(finder.count_with(algo, overlapping))
+}
+
+/// Convenience function to find the nth match in a memory-mapped file
+///
+/// The scan stops as soon as the nth match is found.
+///
+/// # Arguments
+/// * `path` - Path to the file
+/// * `needle` - Bytes to search for
+/// * `n` - Zero-based index of the match, like `Iterator::nth`
+/// * `algo` - Search algorithm to use
+///
+/// # Returns
+/// Position of the nth match, or None if there are fewer matches
+pub fn find_nth_in_file<P: AsRef<Path>>(
+    path: P,
+    needle: &[u8],
+    n: usize,
+    algo: Algorithm,
+) -> Result<Option<usize>, MmapFinderError> {
+    let finder = MmapFinder::new(path, needle.to_vec())?;
+    Ok(finder.find_all(algo).nth(n))
+}
+
 /// Convenience function to search an existing Mmap
 ///
 /// # Arguments
@@ -166,10 +789,5 @@ pub fn find_in_file<P: AsRef<Path>>(
 /// # Returns
 /// Iterator yielding positions of all matches
 pub fn find_in_mmap<'a>(mmap: &'a Mmap, needle: &'a [u8], algo: Algorithm) -> MmapFinderIter<'a> {
-    MmapFinderIter {
-        haystack: mmap,
-        needle,
-        algo,
-        pos: 0,
-    }
+    MmapFinderIter::new(mmap, needle, algo, true)
 }
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use crate::finder::validate_needle;
+use crate::search::{search, Algorithm};
+use crate::{Finder, FinderError, DEFAULT_BUF_SIZE};
+
+/// A streaming finder that checks every buffer window against a small set of needles
+///
+/// Yields `(needle_index, offset)` pairs in offset order. When several needles match
+/// at the same offset they are reported in the order they were given. This simply
+/// runs the chosen single-needle search once per needle, so it is meant for a handful
+/// of needles rather than as a replacement for a multi-pattern automaton. Offsets are
+/// tracked as `u64`, see `positions_u64` for streams past 4GB on 32-bit targets.
+pub struct MultiFinder<R: Read> {
+    haystack: R,
+    needles: Vec<Vec<u8>>,
+    max_len: usize,
+    buffer: Vec<u8>,
+    haystack_pos: u64,
+    buffer_pos: usize,
+    buffer_fill_len: usize,
+    eof: bool,
+    pending: VecDeque<(usize, u64)>,
+    algo: Algorithm,
+}
+
+impl<R: Read> Finder<R> {
+    /// Creates a finder that searches for several independent needles in one pass
+    ///
+    /// # Arguments
+    /// * `haystack` - The source to read from and search in
+    /// * `needles` - Byte patterns to search for, reported by their index in this vector
+    /// * `algo` - Optional search algorithm to use, defaults to Naive
+    pub fn with_needles(
+        haystack: R,
+        needles: Vec<Vec<u8>>,
+        algo: Option<Algorithm>,
+    ) -> Result<MultiFinder<R>, FinderError> {
+        if needles.is_empty() {
+            return Err(FinderError::EmptyNeedle);
+        }
+        // The buffer grows with the longest needle, so only the needles are checked
+        for needle in &needles {
+            validate_needle(needle, usize::MAX)?;
+        }
+        let max_len = needles.iter().map(Vec::len).max().unwrap_or(1);
+        Ok(MultiFinder {
+            haystack,
+            needles,
+            max_len,
+            buffer: vec![0; DEFAULT_BUF_SIZE + max_len - 1],
+            haystack_pos: 0,
+            buffer_pos: 0,
+            buffer_fill_len: 0,
+            eof: false,
+            pending: VecDeque::new(),
+            algo: algo.unwrap_or(Algorithm::Naive),
+        })
+    }
+}
+
+impl<R: Read> MultiFinder<R> {
+    /// Iterates over `(needle_index, position)` pairs with `u64` offsets
+    ///
+    /// Yields the same matches as iterating the finder itself, but positions past 4GB
+    /// are reported as they are on 32-bit targets, where the `usize` iterator returns
+    /// an `InvalidData` error for them instead.
+    pub fn positions_u64(mut self) -> impl Iterator<Item = io::Result<(usize, u64)>> {
+        std::iter::from_fn(move || self.next_u64())
+    }
+
+    /// Finds the next match, the iterator and `positions_u64` both build on this
+    fn next_u64(&mut self) -> Option<io::Result<(usize, u64)>> {
+        loop {
+            if let Some(found) = self.pending.pop_front() {
+                return Some(Ok(found));
+            }
+
+            // Only positions where the longest needle fits are decided until EOF,
+            // otherwise a short needle could be reported ahead of a long one.
+            let decided_end = if self.eof {
+                self.buffer_fill_len
+            } else {
+                (self.buffer_fill_len + 1).saturating_sub(self.max_len)
+            };
+
+            if self.buffer_pos < decided_end {
+                let mut best: Option<usize> = None;
+                let mut hits = Vec::new();
+                for (idx, needle) in self.needles.iter().enumerate() {
+                    let end = (decided_end - 1 + needle.len()).min(self.buffer_fill_len);
+                    let search_area = &self.buffer[self.buffer_pos..end];
+                    if let Some(i) = search(self.algo, search_area, needle) {
+                        match best {
+                            Some(b) if i > b => {}
+                            Some(b) if i == b => hits.push(idx),
+                            _ => {
+                                best = Some(i);
+                                hits.clear();
+                                hits.push(idx);
+                            }
+                        }
+                    }
+                }
+
+                if let Some(i) = best {
+                    let match_pos = self.buffer_pos + i;
+                    self.pending.extend(
+                        hits.into_iter()
+                            .map(|idx| (idx, self.haystack_pos + match_pos as u64)),
+                    );
+                    self.buffer_pos = match_pos + 1;
+                    continue;
+                }
+                self.buffer_pos = decided_end;
+            }
+
+            if self.eof {
+                return None;
+            }
+
+            // Carry the undecided tail (at most max_len - 1 bytes) to the front
+            self.buffer
+                .copy_within(self.buffer_pos..self.buffer_fill_len, 0);
+            self.buffer_fill_len -= self.buffer_pos;
+            self.haystack_pos += self.buffer_pos as u64;
+            self.buffer_pos = 0;
+            match self.haystack.read(&mut self.buffer[self.buffer_fill_len..]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buffer_fill_len += n,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Iterator implementation that yields `(needle_index, position)` for each match
+impl<R: Read> Iterator for MultiFinder<R> {
+    type Item = io::Result<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_u64().map(|found| {
+            let (idx, pos) = found?;
+            let pos = usize::try_from(pos).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "match offset does not fit in usize, use MultiFinder::positions_u64",
+                )
+            })?;
+            Ok((idx, pos))
+        })
+    }
+}
@@ -4,6 +4,10 @@ use std::time::Instant;
 #[cfg(feature = "debug")]
 use tracing::{info, instrument, span, Level};
 
+use alloc::vec::Vec;
+
+use super::kmp::kmp_prefix_table;
+
 /// Boyer-Moore-Horspool search (simple implementation).
 ///
 /// # Arguments
@@ -20,15 +24,30 @@ pub fn bmh_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         return None;
     }
 
-    #[cfg(feature = "debug")]
-    let start_time = Instant::now();
+    bmh_search_with_table(haystack, needle, &bad_char_table(needle))
+}
 
-    // Build bad-character shift table
-    let mut shift = [m; 256usize];
-    for i in 0..m - 1 {
-        shift[needle[i] as usize] = m - 1 - i;
+/// Boyer-Moore-Horspool search with a bad-character table built by `bad_char_table`
+///
+/// Lets repeated searches for the same needle build the table once.
+pub(crate) fn bmh_search_with_table(
+    haystack: &[u8],
+    needle: &[u8],
+    shift: &[usize; 256],
+) -> Option<usize> {
+    let m = needle.len();
+    if m == 0 || haystack.len() < m {
+        return None;
     }
 
+    // Single byte needle - the shift table would be all ones, just scan for the byte
+    if m == 1 {
+        return haystack.iter().position(|&b| b == needle[0]);
+    }
+
+    #[cfg(feature = "debug")]
+    let start_time = Instant::now();
+
     let mut i = 0usize;
     while i + m <= haystack.len() {
         let mut j = (m - 1) as isize;
@@ -52,6 +71,62 @@ pub fn bmh_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     None
 }
 
+/// Builds the bad-character shift table, indexed by the haystack byte under the
+/// last needle position
+pub(crate) fn bad_char_table(needle: &[u8]) -> [usize; 256] {
+    let m = needle.len();
+    let mut shift = [m; 256usize];
+    for i in 0..m - 1 {
+        shift[needle[i] as usize] = m - 1 - i;
+    }
+    shift
+}
+
+/// Finds all (overlapping) matches with Boyer-Moore-Horspool and the Galil rule
+///
+/// After a match the window moves by the needle's period, and the first
+/// `len - period` bytes of the new window are already known to match, so only the
+/// last `period` bytes are compared. This keeps periodic needles such as
+/// `b"abcabcabc"` in `abcabc...` linear, where restarting `bmh_search` after every
+/// match compares the whole needle each time.
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `needle` - The pattern to search for
+///
+/// # Returns
+/// Positions of all matches, empty if the needle is empty
+pub fn bmh_find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    let m = needle.len();
+    let mut positions = Vec::new();
+    if m == 0 || haystack.len() < m {
+        return positions;
+    }
+
+    let shift = bad_char_table(needle);
+    // Smallest period: any two overlapping matches are at least this far apart
+    let period = m - kmp_prefix_table(needle)[m - 1];
+
+    let mut i = 0usize;
+    // Length of the needle prefix already known to match at alignment `i`
+    let mut known = 0usize;
+    while i + m <= haystack.len() {
+        let mut j = m;
+        while j > known && haystack[i + j - 1] == needle[j - 1] {
+            j -= 1;
+        }
+        if j == known {
+            positions.push(i);
+            i += period;
+            known = m - period;
+        } else {
+            i += shift[haystack[i + m - 1] as usize];
+            known = 0;
+        }
+    }
+    positions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,4 +185,22 @@ mod tests {
         let needle = b"b";
         assert_eq!(bmh_search(haystack, needle), Some(1));
     }
+
+    #[test]
+    fn test_single_character_at_last_byte() {
+        assert_eq!(bmh_search(b"aaab", b"b"), Some(3));
+        assert_eq!(bmh_search(b"xxxa", b"a"), Some(3));
+        assert_eq!(bmh_search(b"a", b"a"), Some(0));
+        assert_eq!(bmh_search(b"xxxx", b"a"), None);
+    }
+
+    #[test]
+    fn test_find_all_periodic_needle() {
+        let haystack = b"abcabcabcabcab";
+        assert_eq!(bmh_find_all(haystack, b"abcabcabc"), vec![0, 3]);
+        assert_eq!(bmh_find_all(b"aaaaa", b"aa"), vec![0, 1, 2, 3]);
+        assert_eq!(bmh_find_all(b"abaababaab", b"abaab"), vec![0, 5]);
+        assert_eq!(bmh_find_all(b"abcab", b"b"), vec![1, 4]);
+        assert_eq!(bmh_find_all(b"abc", b""), Vec::<usize>::new());
+    }
 }
@@ -4,6 +4,8 @@ use std::time::Instant;
 #[cfg(feature = "debug")]
 use tracing::{info, instrument, span, Level};
 
+use alloc::vec::Vec;
+
 /// Knuth-Morris-Pratt search implementation.
 ///
 /// Uses a prefix table to efficiently skip comparisons when mismatches occur.
@@ -26,10 +28,29 @@ pub fn kmp_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     #[cfg(feature = "debug")]
     let start_time = Instant::now();
 
-    // Build prefix table (failure function)
-    let mut prefix = vec![0; m];
+    let result = kmp_search_with_table(haystack, needle, &kmp_prefix_table(needle));
+
+    #[cfg(feature = "debug")]
+    if let Some(pos) = result {
+        info!("Match found at position {}", pos);
+        info!(
+            "kmp_search () profiling: total time {:?}",
+            start_time.elapsed()
+        );
+    }
+
+    result
+}
+
+/// Builds the KMP prefix table (failure function) for `needle`
+///
+/// `table[i]` is the length of the longest proper prefix of `needle[..=i]` that is
+/// also a suffix of it. Build it once and pass it to `kmp_search_with_table` when
+/// searching for the same needle repeatedly.
+pub fn kmp_prefix_table(needle: &[u8]) -> Vec<usize> {
+    let mut prefix = vec![0; needle.len()];
     let mut j = 0;
-    for i in 1..m {
+    for i in 1..needle.len() {
         while j > 0 && needle[i] != needle[j] {
             j = prefix[j - 1];
         }
@@ -38,8 +59,27 @@ pub fn kmp_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         }
         prefix[i] = j;
     }
+    prefix
+}
+
+/// Knuth-Morris-Pratt search with a prefix table built by `kmp_prefix_table`
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `needle` - The pattern to search for
+/// * `prefix` - Prefix table of `needle`
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If no match is found or needle is empty
+pub fn kmp_search_with_table(haystack: &[u8], needle: &[u8], prefix: &[usize]) -> Option<usize> {
+    let n = haystack.len();
+    let m = needle.len();
+    if m == 0 || n < m {
+        return None;
+    }
+    debug_assert_eq!(prefix.len(), m, "prefix table does not belong to needle");
 
-    // Search
     let mut i = 0;
     let mut k = 0;
     while i < n {
@@ -47,16 +87,7 @@ pub fn kmp_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
             i += 1;
             k += 1;
             if k == m {
-                let pos = i - m;
-                #[cfg(feature = "debug")]
-                {
-                    info!("Match found at position {}", pos);
-                    info!(
-                        "kmp_search () profiling: total time {:?}",
-                        start_time.elapsed()
-                    );
-                }
-                return Some(pos);
+                return Some(i - m);
             }
         } else if k > 0 {
             k = prefix[k - 1];
@@ -125,4 +156,25 @@ mod tests {
         let needle = b"b";
         assert_eq!(kmp_search(haystack, needle), Some(1));
     }
+
+    #[test]
+    fn test_prefix_table() {
+        assert_eq!(kmp_prefix_table(b"abab"), vec![0, 0, 1, 2]);
+        assert_eq!(kmp_prefix_table(b"aabaaa"), vec![0, 1, 0, 1, 2, 2]);
+        assert_eq!(kmp_prefix_table(b""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_with_table_matches_kmp_search() {
+        let haystack = b"abaababaabaababaababa";
+        for needle in [&b"aba"[..], b"abaab", b"babaab", b"b", b"abababab"] {
+            let table = kmp_prefix_table(needle);
+            for start in 0..haystack.len() {
+                assert_eq!(
+                    kmp_search_with_table(&haystack[start..], needle, &table),
+                    kmp_search(&haystack[start..], needle)
+                );
+            }
+        }
+    }
 }
@@ -4,18 +4,62 @@ mod bmh;
 mod kmp;
 /// Naive (brute force) search implementation
 mod naive;
+/// Fixed-length patterns with byte classes and anchors
+mod pattern;
+/// Shift-And (bitap) search implementation for needles of up to 64 bytes
+mod shift_and;
 /// SIMD-accelerated search implementation using portable SIMD
 mod simd;
 /// SIMD-accelerated search implementation for x86_64 architecture
 #[cfg(target_arch = "x86_64")]
 mod simdx86_64;
+/// Search for patterns containing wildcard bytes
+mod wildcard;
 
-pub use bmh::bmh_search;
-pub use kmp::kmp_search;
-pub use naive::naive_search;
-pub use simd::simd_search;
+pub use bmh::{bmh_find_all, bmh_search};
+pub use kmp::{kmp_prefix_table, kmp_search, kmp_search_with_table};
+pub use naive::{naive_search, naive_search_ascii_ci};
+pub use pattern::{pattern_search, Pattern, PatternError, PATTERN_MAX_LEN};
+pub use shift_and::{shift_and_search, shift_and_search_ascii_ci, SHIFT_AND_MAX_LEN};
+pub use simd::{
+    simd_find_byte, simd_find_byte_iter, simd_search, simd_search_lanes, simd_search_tuned,
+    SimdFindByteIter,
+};
 #[cfg(target_arch = "x86_64")]
 pub use simdx86_64::simd_search_x86_64;
+pub use wildcard::wildcard_search;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Signature shared by every search function: the offset of the first match of the
+/// needle (second argument) in the haystack (first argument), if any
+pub type SearchFn = fn(&[u8], &[u8]) -> Option<usize>;
+
+/// Checks a search function against `naive_search` on the given cases
+///
+/// Meant for validating custom `SearchFn`s, e.g. before passing them to
+/// `Finder::with_custom`. Each case is a `(haystack, needle)` pair.
+///
+/// # Errors
+/// Describes the first case where `f` and `naive_search` disagree
+pub fn differential_check(f: SearchFn, cases: &[(&[u8], &[u8])]) -> Result<(), String> {
+    for (i, &(haystack, needle)) in cases.iter().enumerate() {
+        let expected = naive_search(haystack, needle);
+        let found = f(haystack, needle);
+        if found != expected {
+            return Err(format!(
+                "case {}: found {:?}, naive_search found {:?} for needle \"{}\" in haystack \"{}\"",
+                i,
+                found,
+                expected,
+                needle.escape_ascii(),
+                haystack.escape_ascii()
+            ));
+        }
+    }
+    Ok(())
+}
 
 /// Supported search algorithms
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -23,7 +67,200 @@ pub enum Algorithm {
     Naive,
     Bmh,
     Kmp,
+    /// Bitap search, needles longer than `SHIFT_AND_MAX_LEN` fall back to Naive
+    ShiftAnd,
     #[cfg(target_arch = "x86_64")]
     SimdX8664,
     Simd,
+    /// Picks one of the other algorithms from the needle length at search time
+    Auto,
+}
+
+/// Error returned when parsing an unknown algorithm name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAlgorithmError(String);
+
+impl core::error::Error for ParseAlgorithmError {}
+
+impl core::fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "Unknown algorithm {:?}", self.0)
+    }
+}
+
+impl core::str::FromStr for Algorithm {
+    type Err = ParseAlgorithmError;
+
+    /// Parses an algorithm from its name, case-insensitively
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|algo| algo.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseAlgorithmError(s.to_string()))
+    }
+}
+
+impl core::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Needles up to this length go through the SIMD paths when using `Algorithm::Auto`
+const AUTO_SHORT_NEEDLE_LEN: usize = 16;
+
+impl Algorithm {
+    /// Every algorithm available on the current target
+    pub const ALL: &'static [Algorithm] = &[
+        Algorithm::Naive,
+        Algorithm::Bmh,
+        Algorithm::Kmp,
+        Algorithm::ShiftAnd,
+        #[cfg(target_arch = "x86_64")]
+        Algorithm::SimdX8664,
+        Algorithm::Simd,
+        Algorithm::Auto,
+    ];
+
+    /// Name used by `Display` and `FromStr`
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Naive => "naive",
+            Algorithm::Bmh => "bmh",
+            Algorithm::Kmp => "kmp",
+            Algorithm::ShiftAnd => "shiftand",
+            #[cfg(target_arch = "x86_64")]
+            Algorithm::SimdX8664 => "simdx8664",
+            Algorithm::Simd => "simd",
+            Algorithm::Auto => "auto",
+        }
+    }
+
+    /// Resolves `Algorithm::Auto` to the concrete algorithm used for `needle`
+    ///
+    /// Single bytes use the SIMD byte scan, short needles the SIMD paths and long
+    /// needles Boyer-Moore-Horspool. Any other algorithm is returned unchanged.
+    pub fn resolve(self, needle: &[u8]) -> Algorithm {
+        match self {
+            Algorithm::Auto if needle.len() <= 1 => Algorithm::Simd,
+            #[cfg(target_arch = "x86_64")]
+            Algorithm::Auto if needle.len() <= AUTO_SHORT_NEEDLE_LEN => Algorithm::SimdX8664,
+            #[cfg(not(target_arch = "x86_64"))]
+            Algorithm::Auto if needle.len() <= AUTO_SHORT_NEEDLE_LEN => Algorithm::Simd,
+            Algorithm::Auto => Algorithm::Bmh,
+            algo => algo,
+        }
+    }
+}
+
+/// Cross-checks a SIMD search result against `naive_search` in debug builds
+///
+/// Enabled by the `verify` feature, so SIMD regressions trip an assertion during
+/// development while release builds pay nothing.
+#[cfg(feature = "verify")]
+pub(crate) fn verify_against_naive(
+    name: &str,
+    haystack: &[u8],
+    needle: &[u8],
+    found: Option<usize>,
+) {
+    debug_assert_eq!(
+        found,
+        naive_search(haystack, needle),
+        "{} disagrees with naive_search for needle {:?}",
+        name,
+        needle
+    );
+}
+
+/// Runs the search function selected by `algo`, resolving `Algorithm::Auto` first
+pub(crate) fn search(algo: Algorithm, haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    match algo.resolve(needle) {
+        Algorithm::Naive => naive_search(haystack, needle),
+        Algorithm::Bmh => bmh_search(haystack, needle),
+        Algorithm::Kmp => kmp_search(haystack, needle),
+        Algorithm::ShiftAnd if needle.len() <= SHIFT_AND_MAX_LEN => {
+            shift_and_search(haystack, needle)
+        }
+        Algorithm::ShiftAnd => naive_search(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Algorithm::SimdX8664 => simd_search_x86_64(haystack, needle),
+        Algorithm::Simd | Algorithm::Auto => simd_search(haystack, needle),
+    }
+}
+
+/// ASCII case-insensitive search, see `naive_search_ascii_ci`
+///
+/// Only Naive and Shift-And have case-insensitive variants. `Algorithm::Naive` uses the
+/// naive one, every other algorithm Shift-And, falling back to naive for needles longer
+/// than `SHIFT_AND_MAX_LEN`.
+#[cfg(feature = "std")]
+pub(crate) fn search_ascii_ci(algo: Algorithm, haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    match algo {
+        Algorithm::Naive => naive_search_ascii_ci(haystack, needle),
+        _ if needle.len() <= SHIFT_AND_MAX_LEN => shift_and_search_ascii_ci(haystack, needle),
+        _ => naive_search_ascii_ci(haystack, needle),
+    }
+}
+
+/// Per-needle tables of the selected algorithm, built once for repeated searches
+///
+/// Searching a haystack match by match restarts the search after every match, which
+/// would otherwise rebuild the BMH, KMP or Shift-And tables each time.
+#[derive(Clone)]
+pub(crate) enum PreparedSearch {
+    Bmh([usize; 256]),
+    Kmp(Vec<usize>),
+    ShiftAnd([u64; 256]),
+    /// Algorithms without tables, run through `search`
+    Plain(Algorithm),
+}
+
+impl PreparedSearch {
+    /// Builds the tables `algo` needs for `needle`, resolving `Algorithm::Auto` first
+    pub(crate) fn new(algo: Algorithm, needle: &[u8]) -> Self {
+        match algo.resolve(needle) {
+            Algorithm::Bmh if !needle.is_empty() => Self::Bmh(bmh::bad_char_table(needle)),
+            Algorithm::Kmp => Self::Kmp(kmp_prefix_table(needle)),
+            Algorithm::ShiftAnd if (1..=SHIFT_AND_MAX_LEN).contains(&needle.len()) => {
+                Self::ShiftAnd(shift_and::shift_and_masks(needle, false))
+            }
+            algo => Self::Plain(algo),
+        }
+    }
+
+    /// Searches `haystack` for the needle the tables were built for
+    pub(crate) fn search(&self, haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        match self {
+            Self::Bmh(shift) => bmh::bmh_search_with_table(haystack, needle, shift),
+            Self::Kmp(prefix) => kmp_search_with_table(haystack, needle, prefix),
+            Self::ShiftAnd(masks) => shift_and::shift_and_scan(haystack, needle.len(), masks),
+            Self::Plain(algo) => search(*algo, haystack, needle),
+        }
+    }
+}
+
+/// Finds all, possibly overlapping, occurrences of `needle` in an in-memory haystack
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `needle` - The pattern to search for
+/// * `algo` - Search algorithm to use
+///
+/// # Returns
+/// Positions of all matches, empty if the needle is empty
+pub fn find_all_in_slice(haystack: &[u8], needle: &[u8], algo: Algorithm) -> Vec<usize> {
+    // BMH remembers what it matched between matches, see `bmh_find_all`
+    if algo.resolve(needle) == Algorithm::Bmh {
+        return bmh_find_all(haystack, needle);
+    }
+    let prepared = PreparedSearch::new(algo, needle);
+    let mut positions = Vec::new();
+    let mut pos = 0;
+    while let Some(i) = prepared.search(&haystack[pos..], needle) {
+        positions.push(pos + i);
+        pos += i + 1;
+    }
+    positions
 }
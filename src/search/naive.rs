@@ -7,8 +7,9 @@ use tracing::{info, instrument, span, Level};
 /// A simple, naive byte-slice search that returns the index of the first
 /// occurrence of `needle` inside `haystack`, or `None` if not found.
 ///
-/// This mirrors the previous behaviour using `.windows(...).position(...)`
-/// but is extracted so the algorithm can be named and replaced later.
+/// Scans for the first byte of the needle and compares the rest only at
+/// positions starting with it, with no tables or other preprocessing. The
+/// baseline the other algorithms are checked against.
 #[cfg_attr(feature = "debug", instrument(skip(haystack, needle)))]
 pub fn naive_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     if needle.is_empty() || haystack.len() < needle.len() {
@@ -19,7 +20,20 @@ pub fn naive_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     let start_time = Instant::now();
 
     let last_start = haystack.len() - needle.len();
-    let result = (0..=last_start).find(|&i| &haystack[i..i + needle.len()] == needle);
+    let (first, rest) = (needle[0], &needle[1..]);
+    let mut result = None;
+    let mut i = 0;
+    while let Some(offset) = haystack[i..=last_start].iter().position(|&b| b == first) {
+        i += offset;
+        if &haystack[i + 1..i + needle.len()] == rest {
+            result = Some(i);
+            break;
+        }
+        if i == last_start {
+            break;
+        }
+        i += 1;
+    }
 
     #[cfg(feature = "debug")]
     if let Some(pos) = result {
@@ -33,6 +47,19 @@ pub fn naive_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     result
 }
 
+/// ASCII case-insensitive variant of `naive_search`
+///
+/// ASCII letters match in either case, all other bytes, including those above 0x7f,
+/// must be equal.
+pub fn naive_search_ascii_ci(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +118,28 @@ mod tests {
         let needle = b"b";
         assert_eq!(naive_search(haystack, needle), Some(1));
     }
+
+    #[test]
+    fn test_first_byte_candidates() {
+        let haystack = b"aaaaaaaaab";
+        assert_eq!(naive_search(haystack, b"ab"), Some(8));
+        assert_eq!(naive_search(haystack, b"b"), Some(9));
+        assert_eq!(naive_search(haystack, b"aab"), Some(7));
+        assert_eq!(naive_search(haystack, b"ba"), None);
+        assert_eq!(naive_search(haystack, haystack), Some(0));
+    }
+
+    #[test]
+    fn test_ascii_ci() {
+        assert_eq!(naive_search_ascii_ci(b"say hello", b"HELLO"), Some(4));
+        assert_eq!(naive_search_ascii_ci(b"SAY HeLLo", b"hello"), Some(4));
+        assert_eq!(naive_search_ascii_ci(b"hi", b"HELLO"), None);
+        assert_eq!(naive_search_ascii_ci(b"hello", b""), None);
+        // Only ASCII letters fold
+        assert_eq!(naive_search_ascii_ci(b"a[b", b"A{B"), None);
+        assert_eq!(
+            naive_search_ascii_ci("xÉx".as_bytes(), "é".as_bytes()),
+            None
+        );
+    }
 }
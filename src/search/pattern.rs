@@ -0,0 +1,370 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::shift_and::{shift_and_scan, SHIFT_AND_MAX_LEN};
+
+/// Longest match a `{n}` count may grow a pattern to, the same as `MAX_NEEDLE_LEN`
+pub const PATTERN_MAX_LEN: usize = 8 * 1024;
+
+/// Set of byte values, one bit per value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    const EMPTY: ByteSet = ByteSet([0; 4]);
+    const ALL: ByteSet = ByteSet([u64::MAX; 4]);
+
+    fn single(b: u8) -> Self {
+        let mut set = Self::EMPTY;
+        set.insert_range(b, b);
+        set
+    }
+
+    fn insert_range(&mut self, lo: u8, hi: u8) {
+        for b in lo..=hi {
+            self.0[b as usize / 64] |= 1 << (b % 64);
+        }
+    }
+
+    fn contains(&self, b: u8) -> bool {
+        self.0[b as usize / 64] & (1 << (b % 64)) != 0
+    }
+
+    fn negate(self) -> Self {
+        ByteSet(self.0.map(|bits| !bits))
+    }
+}
+
+/// Error returned when compiling an invalid pattern, indices are byte offsets into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternError {
+    /// The pattern matches no bytes, e.g. `""` or `"^$"`
+    Empty,
+    /// The class opened by `[` at `index` is never closed
+    UnclosedClass { index: usize },
+    /// The range ending at `index` in a class is reversed, like `z-a`
+    InvalidRange { index: usize },
+    /// `{` at `index` does not start a count like `{4}` after a byte, `.` or class, or
+    /// the count would make matches longer than `PATTERN_MAX_LEN`
+    InvalidRepeat { index: usize },
+    /// `^` or `$` at `index` is not at the start or end of the pattern
+    MisplacedAnchor { index: usize },
+    /// The pattern ends with a lone `\`
+    TrailingEscape,
+}
+
+impl core::error::Error for PatternError {}
+
+impl core::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            PatternError::Empty => f.write_str("Pattern matches no bytes"),
+            PatternError::UnclosedClass { index } => {
+                write!(f, "Unclosed character class at position {}", index)
+            }
+            PatternError::InvalidRange { index } => {
+                write!(f, "Reversed range in character class at position {}", index)
+            }
+            PatternError::InvalidRepeat { index } => {
+                write!(f, "Invalid repeat count at position {}", index)
+            }
+            PatternError::MisplacedAnchor { index } => write!(
+                f,
+                "Anchor at position {} must start or end the pattern",
+                index
+            ),
+            PatternError::TrailingEscape => f.write_str("Pattern ends with a lone '\\'"),
+        }
+    }
+}
+
+/// A compiled pattern for `pattern_search`
+///
+/// Patterns are a small, regex-like language whose matches all have the same length:
+/// * literal bytes, with `\` escaping the byte after it, e.g. `\.` or `\[`
+/// * `.` matching any byte
+/// * classes such as `[0-9a-f]`, negated with `[^...]`
+/// * a fixed count after a byte, `.` or class, e.g. `[0-9]{4}`, as long as the match
+///   stays within `PATTERN_MAX_LEN` bytes
+/// * `^` at the start anchors matches to the start of the haystack, `$` at the end to
+///   its end
+///
+/// There are no alternatives and no variable repeats, so searching needs no
+/// backtracking. Patterns of up to `SHIFT_AND_MAX_LEN` bytes run as a Shift-And
+/// automaton with a bitmask per byte value, longer ones are checked position by position.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    classes: Vec<ByteSet>,
+    anchored_start: bool,
+    anchored_end: bool,
+    /// Shift-And table, for patterns short enough
+    masks: Option<Box<[u64; 256]>>,
+}
+
+impl Pattern {
+    /// Compiles a pattern, see `Pattern` for the syntax
+    ///
+    /// # Errors
+    /// Returns `PatternError` if the pattern is malformed or matches no bytes
+    pub fn new<T: AsRef<[u8]>>(pattern: T) -> Result<Self, PatternError> {
+        let p = pattern.as_ref();
+        let anchored_start = p.first() == Some(&b'^');
+        let mut anchored_end = false;
+        let mut classes = Vec::new();
+
+        let mut i = usize::from(anchored_start);
+        while i < p.len() {
+            let set = match p[i] {
+                b'$' if i == p.len() - 1 => {
+                    anchored_end = true;
+                    break;
+                }
+                b'^' | b'$' => return Err(PatternError::MisplacedAnchor { index: i }),
+                b'{' => return Err(PatternError::InvalidRepeat { index: i }),
+                b'.' => {
+                    i += 1;
+                    ByteSet::ALL
+                }
+                b'[' => parse_class(p, &mut i)?,
+                b'\\' => {
+                    let b = *p.get(i + 1).ok_or(PatternError::TrailingEscape)?;
+                    i += 2;
+                    ByteSet::single(b)
+                }
+                b => {
+                    i += 1;
+                    ByteSet::single(b)
+                }
+            };
+            let count = match p.get(i) {
+                Some(b'{') => {
+                    parse_repeat(p, &mut i, PATTERN_MAX_LEN.saturating_sub(classes.len()))?
+                }
+                _ => 1,
+            };
+            classes.extend(core::iter::repeat_n(set, count));
+        }
+
+        if classes.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        let masks = (classes.len() <= SHIFT_AND_MAX_LEN).then(|| {
+            let mut masks = Box::new([0u64; 256]);
+            for (i, set) in classes.iter().enumerate() {
+                for b in 0..=u8::MAX {
+                    if set.contains(b) {
+                        masks[b as usize] |= 1 << i;
+                    }
+                }
+            }
+            masks
+        });
+
+        Ok(Self {
+            classes,
+            anchored_start,
+            anchored_end,
+            masks,
+        })
+    }
+
+    /// Number of bytes every match spans
+    pub fn match_len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Whether the pattern matches `haystack` at `pos`, which must leave room for a match
+    fn matches_at(&self, haystack: &[u8], pos: usize) -> bool {
+        self.classes
+            .iter()
+            .zip(&haystack[pos..])
+            .all(|(set, &b)| set.contains(b))
+    }
+}
+
+impl core::str::FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Parses the class starting with the `[` at `*i`, leaving `*i` after its `]`
+fn parse_class(p: &[u8], i: &mut usize) -> Result<ByteSet, PatternError> {
+    let start = *i;
+    let mut j = start + 1;
+    let negated = p.get(j) == Some(&b'^');
+    if negated {
+        j += 1;
+    }
+
+    let mut set = ByteSet::EMPTY;
+    // A `]` right after the opening bracket is a literal
+    let mut first = true;
+    loop {
+        let (lo, next) = match p.get(j) {
+            None => return Err(PatternError::UnclosedClass { index: start }),
+            Some(b']') if !first => break,
+            Some(b'\\') => (*p.get(j + 1).ok_or(PatternError::TrailingEscape)?, j + 2),
+            Some(&b) => (b, j + 1),
+        };
+        first = false;
+        j = next;
+
+        // `-` between two bytes makes a range, elsewhere it is a literal
+        let hi = match (p.get(j), p.get(j + 1)) {
+            (Some(b'-'), Some(&b)) if b != b']' => {
+                let (hi, next) = match b {
+                    b'\\' => (*p.get(j + 2).ok_or(PatternError::TrailingEscape)?, j + 3),
+                    b => (b, j + 2),
+                };
+                if hi < lo {
+                    return Err(PatternError::InvalidRange { index: next - 1 });
+                }
+                j = next;
+                hi
+            }
+            _ => lo,
+        };
+        set.insert_range(lo, hi);
+    }
+
+    *i = j + 1;
+    Ok(if negated { set.negate() } else { set })
+}
+
+/// Parses the count in `{n}` starting at `*i`, leaving `*i` after the `}`
+///
+/// Counts above `max` are rejected before anything is allocated for them.
+fn parse_repeat(p: &[u8], i: &mut usize, max: usize) -> Result<usize, PatternError> {
+    let start = *i;
+    let invalid = PatternError::InvalidRepeat { index: start };
+    let len = p[start + 1..]
+        .iter()
+        .position(|&b| b == b'}')
+        .ok_or(invalid)?;
+    let digits = &p[start + 1..start + 1 + len];
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(invalid);
+    }
+    let count = digits.iter().try_fold(0usize, |n, &d| {
+        n.checked_mul(10)?.checked_add((d - b'0') as usize)
+    });
+    *i = start + len + 2;
+    count.filter(|&n| n <= max).ok_or(invalid)
+}
+
+/// Finds the first match of a compiled pattern
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `pattern` - The pattern to search for, see `Pattern`
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match, which spans `pattern.match_len()` bytes
+/// * `None` - If no match is found
+pub fn pattern_search(haystack: &[u8], pattern: &Pattern) -> Option<usize> {
+    let m = pattern.match_len();
+    let last_start = haystack.len().checked_sub(m)?;
+    match (pattern.anchored_start, pattern.anchored_end) {
+        (true, true) => (last_start == 0 && pattern.matches_at(haystack, 0)).then_some(0),
+        (true, false) => pattern.matches_at(haystack, 0).then_some(0),
+        (false, true) => pattern
+            .matches_at(haystack, last_start)
+            .then_some(last_start),
+        (false, false) => match &pattern.masks {
+            Some(masks) => shift_and_scan(haystack, m, masks),
+            None => (0..=last_start).find(|&pos| pattern.matches_at(haystack, pos)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn search(haystack: &[u8], pattern: &str) -> Option<usize> {
+        pattern_search(haystack, &Pattern::new(pattern).unwrap())
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(search(b"hello world", "world"), Some(6));
+        assert_eq!(search(b"hello world", "worlds"), None);
+        assert_eq!(search(b"a.b", r"\."), Some(1));
+        assert_eq!(search(b"x[y", r"\["), Some(1));
+    }
+
+    #[test]
+    fn test_anchors() {
+        let haystack = b"MAGIC header MAGIC";
+        assert_eq!(search(haystack, "^MAGIC"), Some(0));
+        assert_eq!(search(haystack, "^header"), None);
+        assert_eq!(search(haystack, "MAGIC$"), Some(13));
+        assert_eq!(search(haystack, "header$"), None);
+        assert_eq!(search(b"MAGIC", "^MAGIC$"), Some(0));
+        assert_eq!(search(haystack, "^MAGIC$"), None);
+        assert_eq!(search(b"MAG", "^MAGIC"), None);
+        // Escaped anchors are literals
+        assert_eq!(search(b"a^b$", r"a\^b\$"), Some(0));
+    }
+
+    #[test]
+    fn test_classes() {
+        let haystack = b"order 12a, year 2024.";
+        assert_eq!(search(haystack, "[0-9]{4}"), Some(16));
+        assert_eq!(search(haystack, "[0-9][0-9][a-f]"), Some(6));
+        assert_eq!(search(haystack, "[0-9]{5}"), None);
+        assert_eq!(search(haystack, "[^a-z ]{3}"), Some(16));
+        assert_eq!(search(haystack, "r ....,"), None);
+        assert_eq!(search(haystack, "r ...,"), Some(4));
+        assert_eq!(search(b"a]b-c", "[]-]"), Some(1));
+        assert_eq!(search(b"abc-", "[a-]{2}"), None);
+        let high = Pattern::new(b"[\x80-\xff]").unwrap();
+        assert_eq!(pattern_search(b"x\x00\xff", &high), Some(2));
+        assert_eq!(search(b"x\xff", "[^x]$"), Some(1));
+    }
+
+    #[test]
+    fn test_long_patterns_match_short_path() {
+        let mut haystack = b"ab".repeat(100);
+        haystack.extend_from_slice(b"0123456789");
+        // Past SHIFT_AND_MAX_LEN positions, checked without the bitmask table
+        let long = Pattern::new("[ab]{70}[0-9]{3}").unwrap();
+        assert!(long.masks.is_none());
+        assert_eq!(long.match_len(), 73);
+        assert_eq!(pattern_search(&haystack, &long), Some(200 - 70));
+        assert_eq!(search(&haystack, "[ab]{60}[0-9]{3}"), Some(200 - 60));
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases: Vec<(&str, PatternError)> = vec![
+            ("", PatternError::Empty),
+            ("^$", PatternError::Empty),
+            ("a{0}", PatternError::Empty),
+            ("ab[cd", PatternError::UnclosedClass { index: 2 }),
+            ("[z-a]", PatternError::InvalidRange { index: 3 }),
+            ("{3}", PatternError::InvalidRepeat { index: 0 }),
+            ("a{x}", PatternError::InvalidRepeat { index: 1 }),
+            ("a{3", PatternError::InvalidRepeat { index: 1 }),
+            ("a{}", PatternError::InvalidRepeat { index: 1 }),
+            ("a{1000000000}", PatternError::InvalidRepeat { index: 1 }),
+            (
+                "a{99999999999999999999999}",
+                PatternError::InvalidRepeat { index: 1 },
+            ),
+            ("a{8000}b{500}", PatternError::InvalidRepeat { index: 8 }),
+            ("a^b", PatternError::MisplacedAnchor { index: 1 }),
+            ("a$b", PatternError::MisplacedAnchor { index: 1 }),
+            ("ab\\", PatternError::TrailingEscape),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(Pattern::new(pattern).unwrap_err(), expected, "{pattern:?}");
+        }
+        let longest = Pattern::new(alloc::format!("a{{{}}}", PATTERN_MAX_LEN)).unwrap();
+        assert_eq!(longest.match_len(), PATTERN_MAX_LEN);
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(feature = "debug")]
+use tracing::instrument;
+
+/// Longest needle `shift_and_search` handles, one bit of a `u64` state per needle byte
+pub const SHIFT_AND_MAX_LEN: usize = 64;
+
+/// Shift-And (exact bitap) search for needles of up to 64 bytes.
+///
+/// Tracks every partial match at once as bits of a `u64`: bit `i` is set when the
+/// last `i + 1` haystack bytes equal the first `i + 1` needle bytes. Each haystack
+/// byte costs a shift, an or and an and, without branches on mismatches.
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `needle` - The pattern to search for
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If no match is found, the needle is empty or longer than 64 bytes
+#[cfg_attr(feature = "debug", instrument(skip(haystack, needle)))]
+pub fn shift_and_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let m = needle.len();
+    if m == 0 || m > SHIFT_AND_MAX_LEN || haystack.len() < m {
+        return None;
+    }
+
+    shift_and_scan(haystack, m, &shift_and_masks(needle, false))
+}
+
+/// ASCII case-insensitive variant of `shift_and_search`
+///
+/// ASCII letters match in either case, all other bytes, including those above 0x7f,
+/// must be equal. Folding is built into the bitmask table, so the scan costs the same
+/// as a case-sensitive one.
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If no match is found, the needle is empty or longer than 64 bytes
+pub fn shift_and_search_ascii_ci(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let m = needle.len();
+    if m == 0 || m > SHIFT_AND_MAX_LEN || haystack.len() < m {
+        return None;
+    }
+    shift_and_scan(haystack, m, &shift_and_masks(needle, true))
+}
+
+/// Bitmask table for `needle`: `masks[b]` has bit i set when `needle[i]` matches `b`
+pub(crate) fn shift_and_masks(needle: &[u8], ascii_ci: bool) -> [u64; 256] {
+    let mut masks = [0u64; 256];
+    for (i, &b) in needle.iter().enumerate() {
+        if ascii_ci {
+            masks[b.to_ascii_lowercase() as usize] |= 1 << i;
+            masks[b.to_ascii_uppercase() as usize] |= 1 << i;
+        } else {
+            masks[b as usize] |= 1 << i;
+        }
+    }
+    masks
+}
+
+/// Runs the Shift-And automaton for a needle of length `m` over `haystack`
+pub(crate) fn shift_and_scan(haystack: &[u8], m: usize, masks: &[u64; 256]) -> Option<usize> {
+    let found = 1u64 << (m - 1);
+
+    let mut state = 0u64;
+    for (i, &b) in haystack.iter().enumerate() {
+        state = ((state << 1) | 1) & masks[b as usize];
+        if state & found != 0 {
+            return Some(i + 1 - m);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_needle() {
+        let haystack = b"hello world";
+        assert_eq!(shift_and_search(haystack, b""), None);
+    }
+
+    #[test]
+    fn test_needle_longer_than_haystack() {
+        let haystack = b"hi";
+        let needle = b"hello";
+        assert_eq!(shift_and_search(haystack, needle), None);
+    }
+
+    #[test]
+    fn test_no_match() {
+        let haystack = b"hello world";
+        let needle = b"xyz";
+        assert_eq!(shift_and_search(haystack, needle), None);
+    }
+
+    #[test]
+    fn test_match_at_beginning() {
+        let haystack = b"hello world";
+        let needle = b"hello";
+        assert_eq!(shift_and_search(haystack, needle), Some(0));
+    }
+
+    #[test]
+    fn test_match_in_middle() {
+        let haystack = b"hello world";
+        let needle = b"world";
+        assert_eq!(shift_and_search(haystack, needle), Some(6));
+    }
+
+    #[test]
+    fn test_repeating_pattern() {
+        let haystack = b"abababab";
+        let needle = b"aba";
+        assert_eq!(shift_and_search(haystack, needle), Some(0));
+    }
+
+    #[test]
+    fn test_single_character() {
+        let haystack = b"abc";
+        let needle = b"b";
+        assert_eq!(shift_and_search(haystack, needle), Some(1));
+    }
+
+    #[test]
+    fn test_length_cap() {
+        let needle = vec![b'a'; SHIFT_AND_MAX_LEN];
+        let mut haystack = vec![b'b'];
+        haystack.extend_from_slice(&needle);
+        assert_eq!(shift_and_search(&haystack, &needle), Some(1));
+        haystack.push(b'a');
+        assert_eq!(shift_and_search(&haystack, &haystack[1..]), None);
+    }
+
+    #[test]
+    fn test_ascii_ci() {
+        assert_eq!(shift_and_search_ascii_ci(b"say hello", b"HELLO"), Some(4));
+        assert_eq!(shift_and_search_ascii_ci(b"SAY HeLLo", b"hello"), Some(4));
+        assert_eq!(shift_and_search(b"say hello", b"HELLO"), None);
+        // Only ASCII letters fold
+        assert_eq!(shift_and_search_ascii_ci(b"a[b", b"A{B"), None);
+        assert_eq!(
+            shift_and_search_ascii_ci("xÉx".as_bytes(), "é".as_bytes()),
+            None
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use core::simd::{cmp::SimdPartialEq, LaneCount, Simd, SupportedLaneCount};
+use core::simd::{cmp::SimdPartialEq, Simd};
 
 #[cfg(feature = "debug")]
 use std::time::Instant;
@@ -29,17 +29,17 @@ const SIMD_LANES: usize = 16;
 // long story short, we use hardware SIMD size and overload it
 // most likely the botelneck is on computation not on memory access
 // maybe, who knows
-// FIXME: test different sizes per different architectures
+// use `simd_search_tuned` to try different sizes per different architectures
 const SIMD_BOOST: usize = 4;
-const SIMD_SIZE_BOOSTED: usize = (SIMD_LANES * SIMD_BOOST).min(128);
+const SIMD_SIZE_BOOSTED: usize = (SIMD_LANES * SIMD_BOOST).min(MAX_SCAN_WIDTH);
+
+// Widest scan `simd_search_tuned` dispatches to, bitmasks hold at most 64 lanes
+const MAX_SCAN_WIDTH: usize = 64;
 
 /// SIMD scan helper that searches for the first byte of needle in haystack
 ///
 /// Returns the index of a potential match candidate
-fn simd_scan_first_byte<const N: usize>(haystack: &[u8], first_byte: u8) -> Option<usize>
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn simd_scan_first_byte<const N: usize>(haystack: &[u8], first_byte: u8) -> Option<usize> {
     let needle_simd = Simd::<u8, N>::splat(first_byte);
     let mut i = 0;
 
@@ -62,78 +62,347 @@ where
         i += N;
     }
 
-    // Check remaining bytes
+    // Scan the remaining bytes with one load ending at the end of the haystack. Its
+    // first lanes overlap bytes already scanned above and are shifted out of the mask.
+    if i < haystack.len() && haystack.len() >= N {
+        let start = haystack.len() - N;
+        let chunk = Simd::<u8, N>::from_slice(&haystack[start..]);
+        let mask = chunk.simd_eq(needle_simd).to_bitmask() >> (i - start);
+        return (mask != 0).then(|| i + mask.trailing_zeros() as usize);
+    }
+
+    // Haystacks shorter than one vector
     haystack[i..]
         .iter()
         .position(|&b| b == first_byte)
         .map(|pos| i + pos)
 }
 
+/// Finds the first occurrence of `byte` in `haystack` using SIMD
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first occurrence
+/// * `None` - If the byte does not occur
+pub fn simd_find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    simd_scan_first_byte::<SIMD_SIZE_BOOSTED>(haystack, byte)
+}
+
+/// Returns an iterator over every position of `byte` in `haystack`, using SIMD
+pub fn simd_find_byte_iter(haystack: &[u8], byte: u8) -> SimdFindByteIter<'_> {
+    SimdFindByteIter {
+        candidates: AnchorCandidates::new(haystack, byte, byte, 0),
+    }
+}
+
+/// Iterator over the positions of a single byte, see `simd_find_byte_iter`
+pub struct SimdFindByteIter<'a> {
+    candidates: AnchorCandidates<'a, SIMD_SIZE_BOOSTED>,
+}
+
+impl Iterator for SimdFindByteIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.candidates.next()
+    }
+}
+
+/// SIMD scan for `len` consecutive copies of `byte`, used for needles of one repeated byte
+///
+/// Runs are read off the equality bitmask of each chunk and a run reaching the end of a
+/// chunk carries over into the next, so no candidate needs verifying.
+fn simd_scan_byte_run<const N: usize>(haystack: &[u8], byte: u8, len: usize) -> Option<usize> {
+    let byte_simd = Simd::<u8, N>::splat(byte);
+    // Start and length of the run of `byte` ending at the current position
+    let mut run_start = 0;
+    let mut run_len = 0;
+    let mut i = 0;
+
+    while i + N <= haystack.len() {
+        let chunk = Simd::<u8, N>::from_slice(&haystack[i..i + N]);
+        let mask = chunk.simd_eq(byte_simd).to_bitmask();
+        let mut lane = 0;
+        while lane < N {
+            // Number of consecutive set bits from `lane` on
+            let ones = ((!(mask >> lane)).trailing_zeros() as usize).min(N - lane);
+            if ones == 0 {
+                // Run broken, skip to the next set bit if any
+                run_len = 0;
+                match mask >> lane {
+                    0 => break,
+                    rest => lane += rest.trailing_zeros() as usize,
+                }
+                continue;
+            }
+            if run_len == 0 {
+                run_start = i + lane;
+            }
+            run_len += ones;
+            if run_len >= len {
+                return Some(run_start);
+            }
+            lane += ones;
+        }
+        i += N;
+    }
+
+    // Check remaining bytes
+    for (pos, &b) in haystack.iter().enumerate().skip(i) {
+        if b != byte {
+            run_len = 0;
+            continue;
+        }
+        if run_len == 0 {
+            run_start = pos;
+        }
+        run_len += 1;
+        if run_len >= len {
+            return Some(run_start);
+        }
+    }
+    None
+}
+
+/// Bytes roughly ordered from most to least common in text and binary data
+///
+/// Bytes not listed are assumed to be rarer than all listed ones.
+const COMMON_BYTES: &[u8] =
+    b" etaonihsrdlumwycfgpbvk\n\0.,'ETAONIHSRDLUMWYCFGPBVK\r\t\xff-\"?!0123456789:;()xjqzXJQZ";
+
+/// Expected frequency of every byte, higher is more common, see `COMMON_BYTES`
+const BYTE_RANK: [u8; 256] = {
+    let mut rank = [0u8; 256];
+    let mut i = 0;
+    while i < COMMON_BYTES.len() {
+        rank[COMMON_BYTES[i] as usize] = (COMMON_BYTES.len() - i) as u8;
+        i += 1;
+    }
+    rank
+};
+
+/// Picks the positions of two needle bytes to anchor the SIMD scan on, in order
+///
+/// The first anchor is the rarest byte according to `BYTE_RANK`. The second is the
+/// rarest byte at another position, preferring a value different from the first
+/// anchor, which rules out far more candidates than checking the same byte twice.
+/// `needle` must be at least 2 bytes long.
+fn rare_anchors(needle: &[u8]) -> (usize, usize) {
+    let rank = |i: &usize| BYTE_RANK[needle[*i] as usize];
+    let rarest = (0..needle.len()).min_by_key(rank).unwrap_or(0);
+    let second = (0..needle.len())
+        .filter(|&i| i != rarest)
+        .min_by_key(|i| (needle[*i] == needle[rarest], rank(i)))
+        .unwrap_or(needle.len() - 1);
+    (rarest.min(second), rarest.max(second))
+}
+
+/// Candidate positions of a SIMD scan for two anchor bytes `gap` positions apart
+///
+/// Compares one chunk against the first anchor and the chunk shifted by `gap` against
+/// the last anchor. The bitmask of the current chunk is kept between calls, so
+/// consecutive candidates within a chunk are yielded without loading it again. A `gap`
+/// of 0 scans for a single byte.
+struct AnchorCandidates<'a, const N: usize> {
+    haystack: &'a [u8],
+    first_byte: u8,
+    last_byte: u8,
+    gap: usize,
+    /// Start of the next chunk to load, or of the next byte to check in the scalar tail
+    pos: usize,
+    /// Start of the chunk `mask` was computed from
+    chunk_start: usize,
+    /// Lanes of the current chunk not yielded yet
+    mask: u64,
+}
+
+impl<'a, const N: usize> AnchorCandidates<'a, N> {
+    fn new(haystack: &'a [u8], first_byte: u8, last_byte: u8, gap: usize) -> Self {
+        Self {
+            haystack,
+            first_byte,
+            last_byte,
+            gap,
+            pos: 0,
+            chunk_start: 0,
+            mask: 0,
+        }
+    }
+}
+
+impl<const N: usize> Iterator for AnchorCandidates<'_, N> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let (haystack, gap) = (self.haystack, self.gap);
+        loop {
+            if self.mask != 0 {
+                let lane = self.mask.trailing_zeros() as usize;
+                // Clear the lowest set bit
+                self.mask &= self.mask - 1;
+                return Some(self.chunk_start + lane);
+            }
+
+            let i = self.pos;
+            if i + gap + N > haystack.len() {
+                break;
+            }
+            let first = Simd::<u8, N>::from_slice(&haystack[i..i + N]);
+            let mut matches = first.simd_eq(Simd::splat(self.first_byte));
+            if gap > 0 {
+                let last = Simd::<u8, N>::from_slice(&haystack[i + gap..i + gap + N]);
+                matches &= last.simd_eq(Simd::splat(self.last_byte));
+            }
+            self.mask = matches.to_bitmask();
+            self.chunk_start = i;
+            self.pos = i + N;
+        }
+
+        // Check remaining positions
+        let end = haystack.len().saturating_sub(gap);
+        while self.pos < end {
+            let pos = self.pos;
+            self.pos += 1;
+            if haystack[pos] == self.first_byte && haystack[pos + gap] == self.last_byte {
+                return Some(pos);
+            }
+        }
+        None
+    }
+}
+
 /// SIMD-based search implementation using portable SIMD
 ///
 /// Uses a two-step approach:
-/// 1. SIMD scan to find candidates matching the first byte
-/// 2. Verification of full needle match at candidate positions
+/// 1. SIMD scan to find candidates matching the two needle bytes expected to be rarest
+/// 2. Verification of the whole needle at candidate positions
+///
+/// Single byte needles are scanned `SIMD_BOOST` vectors at a time while longer needles
+/// use one hardware vector per step, which one `simd_search_lanes::<N>` cannot express.
 #[cfg_attr(feature = "debug", instrument(skip(haystack, needle)))]
 pub fn simd_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    simd_search_tuned(haystack, needle, SIMD_BOOST)
+}
+
+/// SIMD-based search with a custom boost factor for the single byte scan
+///
+/// `simd_search` scans single byte needles `SIMD_BOOST` hardware vectors at a time.
+/// This lets callers pick another factor to benchmark what is fastest on their
+/// hardware. Results are identical for every boost value.
+///
+/// # Arguments
+/// * `haystack` - The byte slice to search in
+/// * `needle` - The byte pattern to search for
+/// * `boost` - Number of hardware vectors per scan step, rounded up to a power of two
+///   and clamped to a scan width between the hardware lane count and 64 bytes
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If the needle is not found
+pub fn simd_search_tuned(haystack: &[u8], needle: &[u8], boost: usize) -> Option<usize> {
+    let found = simd_search_unverified(haystack, needle, boost);
+    #[cfg(feature = "verify")]
+    super::verify_against_naive("simd_search", haystack, needle, found);
+    found
+}
+
+/// SIMD-based search using vectors of `N` lanes for every step
+///
+/// `simd_search` picks the lane count from the target's vector width, this lets callers
+/// choose it at the type level to experiment, e.g. `simd_search_lanes::<32>`. Any `N`
+/// from 1 to 64 works, powers of two map best onto hardware vectors. Larger values
+/// fail to compile when instantiated, as match masks are 64-bit. Results are identical
+/// for every lane count.
+///
+/// # Arguments
+/// * `haystack` - The byte slice to search in
+/// * `needle` - The byte pattern to search for
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If the needle is not found
+pub fn simd_search_lanes<const N: usize>(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let found = match needle.len() {
+        0 => None,
+        len if haystack.len() < len => None,
+        1 => simd_scan_first_byte::<N>(haystack, needle[0]),
+        _ => simd_search_multi_byte::<N>(haystack, needle),
+    };
+    #[cfg(feature = "verify")]
+    super::verify_against_naive("simd_search_lanes", haystack, needle, found);
+    found
+}
+
+/// Scan width in bytes used by `simd_search_tuned` for a boost factor
+fn tuned_width(boost: usize) -> usize {
+    SIMD_LANES
+        .saturating_mul(boost.max(1))
+        .min(MAX_SCAN_WIDTH)
+        .next_power_of_two()
+}
+
+fn simd_search_unverified(haystack: &[u8], needle: &[u8], boost: usize) -> Option<usize> {
     if needle.is_empty() || haystack.len() < needle.len() {
         return None;
     }
 
     // Single byte needle - use SIMD scan directly
     if needle.len() == 1 {
-        return simd_scan_first_byte::<SIMD_SIZE_BOOSTED>(haystack, needle[0]);
+        return match tuned_width(boost) {
+            16 => simd_scan_first_byte::<16>(haystack, needle[0]),
+            32 => simd_scan_first_byte::<32>(haystack, needle[0]),
+            _ => simd_scan_first_byte::<MAX_SCAN_WIDTH>(haystack, needle[0]),
+        };
     }
 
-    let first_byte = needle[0];
-    let mut search_start = 0;
-
-    #[cfg(feature = "debug")]
-    let search_span = span!(Level::INFO, "search_loop").entered();
-
-    while search_start + needle.len() <= haystack.len() {
-        #[cfg(feature = "debug")]
-        let start_time = Instant::now();
-        // Use SIMD to find next candidate position
-        match simd_scan_first_byte::<SIMD_SIZE_BOOSTED>(&haystack[search_start..], first_byte) {
-            Some(offset) => {
-                let candidate_pos = search_start + offset;
-
-                // Check if we have enough bytes remaining
-                if candidate_pos + needle.len() > haystack.len() {
-                    return None;
-                }
+    simd_search_multi_byte::<SIMD_LANES>(haystack, needle)
+}
 
-                // Verify full match
-                if &haystack[candidate_pos..candidate_pos + needle.len()] == needle {
-                    #[cfg(feature = "debug")]
-                    {
-                        info!("Match found at position {}", candidate_pos);
-
-                        info!(
-                            "simd_search () profiling: total time {:?}",
-                            start_time.elapsed()
-                        );
-                    }
-                    return Some(candidate_pos);
-                }
+/// Searches for a needle of at least two bytes, at most as long as the haystack, using
+/// vectors of `N` lanes
+fn simd_search_multi_byte<const N: usize>(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    // One repeated byte, e.g. zero padding - look for a run of it
+    if needle.iter().all(|&b| b == needle[0]) {
+        return simd_scan_byte_run::<N>(haystack, needle[0], needle.len());
+    }
 
-                // Move past this candidate
-                search_start = candidate_pos + 1;
-            }
-            None => return None,
-        }
+    // Two byte needle - both anchors cover the whole needle, no verification needed
+    if needle.len() == 2 {
+        return AnchorCandidates::<N>::new(haystack, needle[0], needle[1], 1).next();
     }
 
+    let (first, last) = rare_anchors(needle);
+    let gap = last - first;
+    let max_start = haystack.len() - needle.len();
+
+    #[cfg(feature = "debug")]
+    let _search_span = span!(Level::INFO, "search_loop").entered();
     #[cfg(feature = "debug")]
-    drop(search_span);
+    let start_time = Instant::now();
 
-    None
+    // Anchoring on rare bytes means needles starting or ending with a common byte, like a
+    // space in text, verify far less often. Scanning from `first` makes every candidate
+    // the start of a potential match. The iterator resumes within the chunk it stopped
+    // in, so failed candidates never cause the same bytes to be scanned again.
+    let found = AnchorCandidates::<N>::new(&haystack[first..], needle[first], needle[last], gap)
+        .take_while(|&candidate_pos| candidate_pos <= max_start)
+        .find(|&candidate_pos| haystack[candidate_pos..candidate_pos + needle.len()] == *needle);
+
+    #[cfg(feature = "debug")]
+    if let Some(candidate_pos) = found {
+        info!("Match found at position {}", candidate_pos);
+        info!(
+            "simd_search () profiling: total time {:?}",
+            start_time.elapsed()
+        );
+    }
+
+    found
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_empty_needle() {
@@ -190,10 +459,257 @@ mod tests {
         assert_eq!(simd_search(haystack, needle), Some(1));
     }
 
+    #[test]
+    fn test_two_byte_needle() {
+        let haystack = b"hello world";
+        assert_eq!(simd_search(haystack, b"wo"), Some(6));
+        assert_eq!(simd_search(haystack, b"ld"), Some(9));
+        assert_eq!(simd_search(haystack, b"dl"), None);
+    }
+
+    #[test]
+    fn test_scan_pair_across_chunks() {
+        let mut haystack = vec![b'a'; SIMD_LANES * 3];
+        haystack[SIMD_LANES - 1] = b'x';
+        haystack[SIMD_LANES] = b'y';
+        assert_eq!(
+            AnchorCandidates::<SIMD_LANES>::new(&haystack, b'x', b'y', 1).next(),
+            Some(SIMD_LANES - 1)
+        );
+        assert_eq!(
+            AnchorCandidates::<SIMD_LANES>::new(&haystack, b'y', b'x', 1).next(),
+            None
+        );
+        assert_eq!(
+            AnchorCandidates::<SIMD_LANES>::new(&haystack, b'a', b'a', 1).next(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_anchor_candidates_yield_every_position() {
+        // Dense candidates within chunks, across chunk boundaries and in the scalar tail
+        let haystack: Vec<u8> = (0..SIMD_LANES * 3 + 5)
+            .map(|i| if i % 3 == 0 { b'a' } else { b'b' })
+            .collect();
+        for gap in 0..4 {
+            let expected: Vec<usize> = (0..haystack.len() - gap)
+                .filter(|&pos| haystack[pos] == b'a' && haystack[pos + gap] == b'a')
+                .collect();
+            let found: Vec<usize> =
+                AnchorCandidates::<SIMD_LANES>::new(&haystack, b'a', b'a', gap).collect();
+            assert_eq!(found, expected, "gap {gap}");
+        }
+    }
+
+    #[test]
+    fn test_rare_anchors() {
+        // Space is the most common byte, so it is never picked over letters
+        assert_eq!(rare_anchors(b" the fox "), (5, 7));
+        assert_eq!(rare_anchors(b"aaaXaaaa"), (0, 3));
+        // Same rarity everywhere falls back to the leftmost positions
+        assert_eq!(rare_anchors(b"ab"), (0, 1));
+        assert_eq!(rare_anchors(b"zzz"), (0, 1));
+    }
+
+    #[test]
+    fn test_rare_anchor_near_needle_start() {
+        // The anchors sit at the start of the needle, candidates near the end of the
+        // haystack must not run past it
+        let needle = b"qx    ";
+        let haystack = b"  qx     qx  ";
+        assert_eq!(simd_search(haystack, needle), Some(2));
+        assert_eq!(simd_search(&haystack[3..], needle), None);
+        let mut haystack = vec![b' '; SIMD_LANES * 3];
+        haystack[SIMD_LANES * 3 - 3..].copy_from_slice(b"qx ");
+        assert_eq!(simd_search(&haystack, needle), None);
+        haystack[SIMD_LANES..SIMD_LANES + 2].copy_from_slice(b"qx");
+        assert_eq!(simd_search(&haystack, needle), Some(SIMD_LANES));
+    }
+
+    #[test]
+    fn test_equal_byte_needle() {
+        let haystack = b"\x01\0\0\x02\0\0\0\x03\0\0\0\0\x04";
+        assert_eq!(simd_search(haystack, &[0; 4]), Some(8));
+        assert_eq!(simd_search(haystack, &[0; 3]), Some(4));
+        assert_eq!(simd_search(haystack, &[0; 5]), None);
+
+        // Runs spanning chunk boundaries and ending in the scalar tail
+        for len in [
+            2,
+            3,
+            SIMD_LANES - 1,
+            SIMD_LANES,
+            SIMD_LANES + 1,
+            2 * SIMD_LANES + 3,
+        ] {
+            for start in [0, 1, SIMD_LANES - 2, SIMD_LANES + 5] {
+                let mut haystack = vec![b'a'; start + 2 * len + SIMD_LANES + 3];
+                // A shorter run first, which must not match
+                haystack[..len - 1].fill(b'z');
+                haystack[start + len..start + 2 * len].fill(b'z');
+                let needle = vec![b'z'; len];
+                assert_eq!(
+                    simd_search(&haystack, &needle),
+                    crate::search::naive_search(&haystack, &needle),
+                    "len {len} start {start}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_common_first_byte_rare_last_byte() {
+        let mut haystack = b"aXXXXXXXc".repeat(SIMD_LANES);
+        assert_eq!(simd_search(&haystack, b"aXXXXXXXb"), None);
+        haystack.extend_from_slice(b"aXXXXXXXb");
+        assert_eq!(
+            simd_search(&haystack, b"aXXXXXXXb"),
+            Some(haystack.len() - 9)
+        );
+        // Anchors match but the middle differs
+        assert_eq!(simd_search(b"aXXYXXXXb", b"aXXXXXXXb"), None);
+    }
+
     #[test]
     fn test_scan_first_byte() {
         let haystack = b"hello world";
         assert_eq!(simd_scan_first_byte::<SIMD_LANES>(haystack, b'w'), Some(6));
         assert_eq!(simd_scan_first_byte::<SIMD_LANES>(haystack, b'z'), None);
     }
+
+    #[test]
+    fn test_scan_first_byte_tail() {
+        fn check<const N: usize>() {
+            for len in [N - 1, N, N + 1, 2 * N - 1, 2 * N + 1, 3 * N - 2] {
+                for pos in 0..len {
+                    let mut haystack = vec![b'.'; len];
+                    haystack[pos] = b'x';
+                    assert_eq!(simd_scan_first_byte::<N>(&haystack, b'x'), Some(pos));
+                    // A later occurrence in the overlapping load does not hide this one
+                    haystack[len - 1] = b'x';
+                    assert_eq!(simd_scan_first_byte::<N>(&haystack, b'x'), Some(pos));
+                    let after = (pos + 1 < len).then(|| len - pos - 2);
+                    assert_eq!(simd_scan_first_byte::<N>(&haystack[pos + 1..], b'x'), after);
+                }
+                assert_eq!(simd_scan_first_byte::<N>(&vec![b'.'; len], b'x'), None);
+            }
+        }
+        check::<16>();
+        check::<32>();
+        check::<64>();
+    }
+
+    #[test]
+    fn test_find_byte() {
+        let haystack = b"hello world";
+        assert_eq!(simd_find_byte(haystack, b'w'), Some(6));
+        assert_eq!(simd_find_byte(haystack, b'z'), None);
+        assert_eq!(simd_find_byte(b"", b'z'), None);
+    }
+
+    #[test]
+    fn test_find_byte_iter() {
+        assert_eq!(
+            simd_find_byte_iter(b"a.a.a", b'a').collect::<Vec<_>>(),
+            vec![0, 2, 4]
+        );
+        assert_eq!(simd_find_byte_iter(b"a.a.a", b'z').count(), 0);
+
+        let mut haystack = vec![b'.'; SIMD_SIZE_BOOSTED * 2 + 3];
+        let expected = vec![
+            0,
+            SIMD_SIZE_BOOSTED - 1,
+            SIMD_SIZE_BOOSTED,
+            haystack.len() - 1,
+        ];
+        for &i in &expected {
+            haystack[i] = b'a';
+        }
+        assert_eq!(
+            simd_find_byte_iter(&haystack, b'a').collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_tuned_boost_matches_default() {
+        let mut haystack = vec![b'.'; 1000];
+        for &i in &[3, 130, 131, 517, 999] {
+            haystack[i] = b'a';
+        }
+        haystack[518] = b'b';
+        for needle in [&b"a"[..], b"ab", b"a.", b".a.", b"zz"] {
+            let expected = simd_search(&haystack, needle);
+            for boost in [0, 1, 2, 3, 4, 8, 16, usize::MAX] {
+                assert_eq!(
+                    simd_search_tuned(&haystack, needle, boost),
+                    expected,
+                    "boost {} needle {:?}",
+                    boost,
+                    needle
+                );
+                assert_eq!(
+                    simd_search_tuned(&haystack[1..], needle, boost),
+                    simd_search(&haystack[1..], needle)
+                );
+            }
+        }
+        assert_eq!(tuned_width(0), SIMD_LANES);
+        assert_eq!(tuned_width(usize::MAX), MAX_SCAN_WIDTH);
+    }
+
+    #[test]
+    fn test_lanes_match_naive() {
+        let mut haystack = b"the quick brown fox jumps over the lazy dog ".repeat(8);
+        haystack.extend_from_slice(b"\0\0\0zz");
+        let needles: [&[u8]; 9] = [
+            b"",
+            b"t",
+            b"z",
+            b"zz",
+            b"\0\0\0",
+            b"the",
+            b"lazy dog",
+            b"dog \0",
+            b"absent",
+        ];
+        for needle in needles {
+            for start in [0, 1, 15, 33] {
+                let haystack = &haystack[start..];
+                let expected = crate::search::naive_search(haystack, needle);
+                assert_eq!(
+                    simd_search_lanes::<16>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+                assert_eq!(
+                    simd_search_lanes::<32>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+                assert_eq!(
+                    simd_search_lanes::<64>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+                // Lane counts need not be powers of two
+                assert_eq!(
+                    simd_search_lanes::<1>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+                assert_eq!(
+                    simd_search_lanes::<3>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+                assert_eq!(
+                    simd_search_lanes::<24>(haystack, needle),
+                    expected,
+                    "{needle:?}"
+                );
+            }
+        }
+    }
 }
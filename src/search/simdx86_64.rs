@@ -15,14 +15,37 @@ use tracing::{info, instrument, span, Level};
 #[cfg(target_arch = "x86_64")]
 #[cfg_attr(feature = "debug", instrument(skip(haystack, needle)))]
 pub fn simd_search_x86_64(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-    use std::arch::x86_64::*;
+    let found = simd_search_x86_64_unverified(haystack, needle);
+    #[cfg(feature = "verify")]
+    crate::search::verify_against_naive("simd_search_x86_64", haystack, needle, found);
+    found
+}
+
+/// Whether AVX2 is available, detected at runtime with `std` and at compile time without
+#[cfg(target_arch = "x86_64")]
+fn has_avx2() -> bool {
+    #[cfg(feature = "std")]
+    return is_x86_feature_detected!("avx2");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "avx2");
+}
+
+#[cfg(target_arch = "x86_64")]
+fn simd_search_x86_64_unverified(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    use core::arch::x86_64::*;
     // Check if needle is empty or haystack is shorter than needle
     if needle.is_empty() || haystack.len() < needle.len() {
         // Return None immediately since no match is possible
         return None;
     }
 
-    // For needles longer than 16 bytes, fall back to portable SIMD
+    // Needles of 17 to 32 bytes fit a 32 byte AVX2 register
+    if needle.len() > 16 && needle.len() <= 32 && has_avx2() {
+        // SAFETY: AVX2 support was just checked
+        return unsafe { simd_search_avx2(haystack, needle) };
+    }
+
+    // For longer needles, or without AVX2, fall back to portable SIMD
     if needle.len() > 16 {
         return crate::search::simd_search(haystack, needle);
     }
@@ -72,9 +95,14 @@ pub fn simd_search_x86_64(haystack: &[u8], needle: &[u8]) -> Option<usize> {
             i += 1; // Slide by 1 byte for thorough search
         }
 
-        // Fallback for remaining bytes
-        while i + needle.len() <= haystack.len() {
-            // Use slice starts_with for exact match in remaining bytes
+        // Fallback for remaining bytes: jump to the next first needle byte, then verify
+        let last_start = haystack.len() - needle.len();
+        while i <= last_start {
+            let Some(offset) = crate::search::simd_find_byte(&haystack[i..=last_start], needle[0])
+            else {
+                break;
+            };
+            i += offset;
             if haystack[i..].starts_with(needle) {
                 // Match found in tail, return index
                 #[cfg(feature = "debug")]
@@ -87,7 +115,7 @@ pub fn simd_search_x86_64(haystack: &[u8], needle: &[u8]) -> Option<usize> {
                 }
                 return Some(i);
             }
-            // Increment index for byte-by-byte search
+            // Resume after the candidate
             i += 1;
         }
         // End of fallback search
@@ -97,10 +125,41 @@ pub fn simd_search_x86_64(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     None
 }
 
+/// AVX2 variant of the SSE loop above for needles of 17 to 32 bytes
+///
+/// # Safety
+/// The CPU must support AVX2, and `needle` must be 1 to 32 bytes long and no longer
+/// than `haystack`.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn simd_search_avx2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    use core::arch::x86_64::*;
+
+    // Load needle into SIMD register (padded with zeros if shorter than 32)
+    let mut needle_buf = [0u8; 32];
+    needle_buf[..needle.len()].copy_from_slice(needle);
+    let needle_simd = _mm256_loadu_si256(needle_buf.as_ptr() as *const __m256i);
+    let match_mask = u32::MAX >> (32 - needle.len());
+
+    let mut i = 0;
+    while i + 32 <= haystack.len() {
+        let block = _mm256_loadu_si256(haystack[i..].as_ptr() as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(block, needle_simd)) as u32;
+        if (mask & match_mask) == match_mask {
+            return Some(i);
+        }
+        i += 1; // Slide by 1 byte for thorough search
+    }
+
+    // Fallback for remaining bytes
+    (i..=haystack.len() - needle.len()).find(|&i| haystack[i..].starts_with(needle))
+}
+
 #[cfg(test)]
 #[cfg(target_arch = "x86_64")]
 mod tests {
     use super::*;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_empty_needle() {
@@ -156,4 +215,51 @@ mod tests {
         let needle = b"b";
         assert_eq!(simd_search_x86_64(haystack, needle), Some(1));
     }
+
+    #[test]
+    fn test_needles_up_to_32_bytes() {
+        for len in [17, 24, 32, 33] {
+            let needle: Vec<u8> = (0..len as u8).map(|b| b'A' + b % 26).collect();
+            let mut haystack = vec![b'.'; 150];
+            // A near miss differing in the last byte, then matches in the SIMD range
+            // and in the tail
+            haystack[3..3 + len].copy_from_slice(&needle);
+            haystack[3 + len - 1] = b'!';
+            haystack[40..40 + len].copy_from_slice(&needle);
+            let tail = haystack.len() - len;
+            haystack[tail..].copy_from_slice(&needle);
+
+            assert_eq!(
+                simd_search_x86_64(&haystack, &needle),
+                Some(40),
+                "len {}",
+                len
+            );
+            assert_eq!(
+                simd_search_x86_64(&haystack[41..], &needle),
+                Some(tail - 41),
+                "len {}",
+                len
+            );
+            assert_eq!(simd_search_x86_64(&haystack[..40], &needle), None);
+            assert_eq!(simd_search_x86_64(&needle, &needle), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_match_in_tail() {
+        // Haystacks of 17 to 31 bytes, the match starts past the last full 16 byte block
+        for len in 17..32 {
+            let mut haystack = vec![b'.'; len];
+            // A candidate with the right first byte, then the match ending the haystack
+            haystack[len - 7..len - 4].copy_from_slice(b"ab!");
+            haystack[len - 3..].copy_from_slice(b"abc");
+            assert_eq!(
+                simd_search_x86_64(&haystack, b"abc"),
+                Some(len - 3),
+                "len {len}"
+            );
+            assert_eq!(simd_search_x86_64(&haystack, b"abd"), None, "len {len}");
+        }
+    }
 }
@@ -0,0 +1,102 @@
+#[cfg(feature = "debug")]
+use tracing::instrument;
+
+/// Wildcard search where `None` in the pattern matches any byte.
+///
+/// Candidates are located by scanning for the first concrete byte of the pattern,
+/// then the remaining positions are verified.
+///
+/// # Arguments
+/// * `haystack` - The data to search in
+/// * `pattern` - The pattern to search for, `None` entries match any byte
+///
+/// # Returns
+/// * `Some(usize)` - Index of the first match
+/// * `None` - If no match is found or pattern is empty
+#[cfg_attr(feature = "debug", instrument(skip(haystack, pattern)))]
+pub fn wildcard_search(haystack: &[u8], pattern: &[Option<u8>]) -> Option<usize> {
+    let m = pattern.len();
+    if m == 0 || haystack.len() < m {
+        return None;
+    }
+
+    // A pattern made only of wildcards matches right away
+    let Some((anchor, anchor_byte)) = pattern
+        .iter()
+        .enumerate()
+        .find_map(|(i, b)| b.map(|b| (i, b)))
+    else {
+        return Some(0);
+    };
+
+    let last_start = haystack.len() - m;
+    let mut start = 0;
+    while start <= last_start {
+        // Scan for the anchor byte, then verify the whole window around it
+        let offset = haystack[start + anchor..=last_start + anchor]
+            .iter()
+            .position(|&b| b == anchor_byte)?;
+        let candidate = start + offset;
+        if haystack[candidate..candidate + m]
+            .iter()
+            .zip(pattern)
+            .all(|(&h, p)| p.is_none_or(|p| p == h))
+        {
+            return Some(candidate);
+        }
+        start = candidate + 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_pattern() {
+        let haystack = b"hello world";
+        assert_eq!(wildcard_search(haystack, &[]), None);
+    }
+
+    #[test]
+    fn test_pattern_longer_than_haystack() {
+        assert_eq!(wildcard_search(b"hi", &[None, None, None]), None);
+    }
+
+    #[test]
+    fn test_match_with_wildcards() {
+        let haystack = [0xde, 0xad, 0xbe, 0xef];
+        let pattern = [Some(0xde), None, Some(0xbe), Some(0xef)];
+        assert_eq!(wildcard_search(&haystack, &pattern), Some(0));
+    }
+
+    #[test]
+    fn test_no_match() {
+        let haystack = [0xde, 0xad, 0xbe, 0xef];
+        let pattern = [Some(0xde), None, Some(0xef)];
+        assert_eq!(wildcard_search(&haystack, &pattern), None);
+    }
+
+    #[test]
+    fn test_leading_wildcard() {
+        let haystack = b"xxabcab";
+        assert_eq!(wildcard_search(haystack, &[None, Some(b'b')]), Some(2));
+        assert_eq!(wildcard_search(haystack, &[None, Some(b'a')]), Some(1));
+        assert_eq!(wildcard_search(b"ab", &[None, Some(b'a')]), None);
+    }
+
+    #[test]
+    fn test_match_at_end() {
+        let haystack = b"hello world";
+        assert_eq!(
+            wildcard_search(haystack, &[Some(b'w'), None, Some(b'r'), None, Some(b'd')]),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn test_only_wildcards() {
+        assert_eq!(wildcard_search(b"abc", &[None, None]), Some(0));
+    }
+}
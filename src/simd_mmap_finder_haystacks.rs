@@ -1,7 +1,5 @@
 use clap::Parser;
 use simd_needle::{MmapFinder, SearchAlgo};
-use std::fs::File;
-use std::io::BufReader;
 use std::path;
 
 #[cfg(feature = "debug")]
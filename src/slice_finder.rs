@@ -0,0 +1,103 @@
+use crate::finder::{validate_needle, FinderError};
+use crate::mmap_finder::MmapFinderIter;
+use crate::search::{search, Algorithm};
+
+/// Finder over bytes that are already in memory
+///
+/// Offers the same search methods as `MmapFinder` for data such as a decompressed
+/// `Vec<u8>`, without touching the filesystem or going through a `Read` stream.
+pub struct SliceFinder<'a> {
+    haystack: &'a [u8],
+    needle: Vec<u8>,
+}
+
+impl<'a> SliceFinder<'a> {
+    /// Create a new SliceFinder over a byte slice
+    ///
+    /// # Arguments
+    /// * `haystack` - Bytes to search in
+    /// * `needle` - Bytes to search for
+    ///
+    /// # Returns
+    /// Result containing the SliceFinder or an error
+    pub fn new(haystack: &'a [u8], needle: Vec<u8>) -> Result<Self, FinderError> {
+        validate_needle(&needle, usize::MAX)?;
+
+        Ok(Self { haystack, needle })
+    }
+
+    /// Find all occurrences of the needle
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Iterator yielding positions of matches
+    pub fn find_all(&self, algo: Algorithm) -> MmapFinderIter<'_> {
+        self.find_all_with(algo, true)
+    }
+
+    /// Find all occurrences of the needle, choosing whether matches may overlap
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `overlapping` - If false, the search resumes after the end of each match
+    ///
+    /// # Returns
+    /// Iterator yielding positions of matches
+    pub fn find_all_with(&self, algo: Algorithm, overlapping: bool) -> MmapFinderIter<'_> {
+        MmapFinderIter::new(self.haystack, &self.needle, algo, overlapping)
+    }
+
+    /// Count all occurrences of the needle, overlapping matches are counted individually
+    ///
+    /// Use `count_with` to count non-overlapping matches instead.
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Number of matches
+    pub fn count(&self, algo: Algorithm) -> usize {
+        self.count_with(algo, true)
+    }
+
+    /// Count all occurrences of the needle, choosing whether matches may overlap
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    /// * `overlapping` - If false, counting resumes after the end of each match
+    ///
+    /// # Returns
+    /// Number of matches
+    pub fn count_with(&self, algo: Algorithm, overlapping: bool) -> usize {
+        self.find_all_with(algo, overlapping).count()
+    }
+
+    /// Find the first occurrence of the needle
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Option containing the position of the first match, or None if not found
+    pub fn find_first(&self, algo: Algorithm) -> Option<usize> {
+        search(algo, self.haystack, &self.needle)
+    }
+
+    /// Find the last occurrence of the needle
+    ///
+    /// # Arguments
+    /// * `algo` - Search algorithm to use
+    ///
+    /// # Returns
+    /// Option containing the position of the last match, or None if not found
+    pub fn find_last(&self, algo: Algorithm) -> Option<usize> {
+        self.find_all(algo).next_back()
+    }
+
+    /// Get a reference to the underlying data
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.haystack
+    }
+}
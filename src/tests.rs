@@ -1,12 +1,73 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::{search::Algorithm, Finder, FinderTrait, DEFAULT_BUF_SIZE};
     use proptest::prelude::*;
     use std::io::Cursor;
 
+    /// Reader handing out at most `chunk` bytes per `read` call, tracking how much was read
+    struct SlowReader<'a> {
+        data: &'a [u8],
+        chunk: usize,
+        bytes_read: usize,
+    }
+
+    impl<'a> SlowReader<'a> {
+        fn new(data: &'a [u8], chunk: usize) -> Self {
+            Self {
+                data,
+                chunk,
+                bytes_read: 0,
+            }
+        }
+    }
+
+    impl std::io::Read for &mut SlowReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            self.bytes_read += n;
+            Ok(n)
+        }
+    }
+
+    /// Reader that hands out its data once, then fails
+    struct FailingReader<'a>(&'a [u8]);
+
+    impl std::io::Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::Error::other("disk on fire"));
+            }
+            let n = buf.len().min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    /// Reader handing out chunks of the given sizes in turn, cycling through them
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunks: Vec<usize>,
+        next: usize,
+    }
+
+    impl std::io::Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let chunk = self.chunks[self.next % self.chunks.len()];
+            self.next += 1;
+            let n = chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
     fn find_all(haystack: &[u8], needle: &[u8], algo: Algorithm) -> Vec<usize> {
         let finder = Finder::new(Cursor::new(haystack), needle.to_vec(), Some(algo)).unwrap();
-        finder.map(|r| r.unwrap()).collect()
+        finder.collect_all().unwrap()
     }
 
     macro_rules! test_all_algos {
@@ -30,6 +91,12 @@ mod tests {
                     $test_body(algo);
                 }
 
+                #[test]
+                fn [<$test_name _shiftand>]() {
+                    let algo = Algorithm::ShiftAnd;
+                    $test_body(algo);
+                }
+
                 #[test]
                 fn [<$test_name _simdx8664>]() {
                     #[cfg(target_arch = "x86_64")]{
@@ -44,6 +111,12 @@ mod tests {
                     let algo = Algorithm::Simd;
                     $test_body(algo);
                 }
+
+                #[test]
+                fn [<$test_name _auto>]() {
+                    let algo = Algorithm::Auto;
+                    $test_body(algo);
+                }
             }
         };
     }
@@ -76,6 +149,115 @@ mod tests {
         assert_eq!(find_all(b"aaaaa", b"aa", algo), vec![0, 1, 2, 3]);
     });
 
+    test_all_algos!(test_needle_haystack_length_edges, |algo: Algorithm| {
+        // Lengths around the 16 byte SSE and 32 byte AVX2 registers of simdx8664
+        for m in [1, 2, 3, 15, 16, 17, 31, 32, 33] {
+            let haystack: Vec<u8> = (0..=m as u8).map(|b| b'a' + b % 26).collect();
+
+            // Needle one byte shorter than the haystack, at either end
+            let (head, tail) = (&haystack[..m], &haystack[1..]);
+            assert_eq!(find_all(&haystack, head, algo), vec![0], "head, len {}", m);
+            assert_eq!(find_all(&haystack, tail, algo), vec![1], "tail, len {}", m);
+            assert_eq!(crate::find_all_in_slice(&haystack, head, algo), vec![0]);
+            assert_eq!(crate::find_all_in_slice(&haystack, tail, algo), vec![1]);
+
+            // Needle equal to the haystack
+            let equal = &haystack[..m];
+            assert_eq!(find_all(equal, equal, algo), vec![0], "equal, len {}", m);
+            assert_eq!(crate::find_all_in_slice(equal, equal, algo), vec![0]);
+
+            // Needle one byte longer than the haystack
+            assert_eq!(find_all(equal, &haystack, algo), Vec::<usize>::new());
+            assert_eq!(
+                crate::find_all_in_slice(equal, &haystack, algo),
+                Vec::<usize>::new()
+            );
+        }
+    });
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_simd_x86_64_register_sized_needles() {
+        use crate::simd_search_x86_64;
+
+        for m in [16, 32] {
+            let needle: Vec<u8> = (0..m as u8).collect();
+            assert_eq!(simd_search_x86_64(&needle, &needle), Some(0));
+            assert_eq!(simd_search_x86_64(&needle[..m - 1], &needle), None);
+            let mut haystack = vec![0xff];
+            haystack.extend_from_slice(&needle);
+            assert_eq!(simd_search_x86_64(&haystack, &needle), Some(1));
+            assert_eq!(simd_search_x86_64(&haystack, &needle[..m - 1]), Some(1));
+        }
+    }
+
+    test_all_algos!(test_match_at_exact_eof, |algo: Algorithm| {
+        use crate::{MmapFinder, SliceFinder};
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let needle = b"needle!";
+        let m = needle.len();
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&[b'.'; 100]).unwrap();
+        temp_file.write_all(needle).unwrap();
+        temp_file.flush().unwrap();
+        let mmap_finder = MmapFinder::new(temp_file.path(), needle.to_vec()).unwrap();
+        assert_eq!(mmap_finder.find_all(algo).collect::<Vec<_>>(), vec![100]);
+
+        // SliceFinder runs the same iterator as MmapFinder without a file per case
+        for buffer_size in [m, m + 1, DEFAULT_BUF_SIZE] {
+            // Vary the stream length so EOF lands at every offset within a buffer
+            for len in (m..m + 3 * buffer_size.min(64)).chain([DEFAULT_BUF_SIZE * 2 + 3]) {
+                let mut haystack = vec![b'.'; len];
+                haystack[len - m..].copy_from_slice(needle);
+                let expected = SliceFinder::new(&haystack, needle.to_vec())
+                    .unwrap()
+                    .find_all(algo)
+                    .collect::<Vec<_>>();
+                assert_eq!(expected, vec![len - m]);
+
+                for chunk in [1, 3, usize::MAX] {
+                    let mut reader = SlowReader::new(&haystack, chunk);
+                    let finder = Finder::with_buffer_size(
+                        &mut reader,
+                        needle.to_vec(),
+                        buffer_size,
+                        Some(algo),
+                    )
+                    .unwrap();
+                    assert_eq!(
+                        finder.collect_all().unwrap(),
+                        expected,
+                        "buffer {} len {} chunk {}",
+                        buffer_size,
+                        len,
+                        chunk
+                    );
+                }
+            }
+        }
+    });
+
+    test_all_algos!(test_slice_multiple_matches, |algo: Algorithm| {
+        assert_eq!(
+            crate::find_all_in_slice(b"test test test", b"test", algo),
+            vec![0, 5, 10]
+        );
+    });
+
+    test_all_algos!(test_slice_overlapping_matches, |algo: Algorithm| {
+        assert_eq!(
+            crate::find_all_in_slice(b"ababab", b"abab", algo),
+            vec![0, 2]
+        );
+        assert_eq!(
+            crate::find_all_in_slice(b"aaaaa", b"aa", algo),
+            vec![0, 1, 2, 3]
+        );
+    });
+
     test_all_algos!(test_empty_haystack, |algo: Algorithm| {
         assert_eq!(find_all(b"", b"test", algo), Vec::<usize>::new());
     });
@@ -93,6 +275,32 @@ mod tests {
         assert_eq!(results, vec![DEFAULT_BUF_SIZE - 2]);
     });
 
+    test_all_algos!(test_match_after_several_buffers, |algo: Algorithm| {
+        let mut haystack = vec![0; 3 * DEFAULT_BUF_SIZE + 7];
+        haystack.extend_from_slice(b"needle");
+        let expected = vec![3 * DEFAULT_BUF_SIZE + 7];
+        assert_eq!(find_all(&haystack, b"needle", algo), expected);
+
+        let mut reader = SlowReader::new(&haystack, 3);
+        let finder = Finder::new(&mut reader, b"needle".to_vec(), Some(algo)).unwrap();
+        let results: Vec<_> = finder.map(|r| r.unwrap()).collect();
+        assert_eq!(results, expected);
+    });
+
+    test_all_algos!(test_contains_stops_early, |algo: Algorithm| {
+        let mut haystack = b"xxneedlexx".to_vec();
+        haystack.extend_from_slice(&vec![0; 4 * DEFAULT_BUF_SIZE]);
+
+        let mut reader = SlowReader::new(&haystack, 4);
+        let finder = Finder::new(&mut reader, b"needle".to_vec(), Some(algo)).unwrap();
+        assert!(finder.contains().unwrap());
+        assert!(reader.bytes_read < haystack.len());
+        assert!(reader.bytes_read <= 12);
+
+        let finder = Finder::new(Cursor::new(&haystack), b"other".to_vec(), Some(algo)).unwrap();
+        assert!(!finder.contains().unwrap());
+    });
+
     test_all_algos!(test_needle_longer_than_buffer, |algo: Algorithm| {
         let needle = vec![b'a'; DEFAULT_BUF_SIZE * 2];
         let mut haystack = vec![b'b'; 10];
@@ -106,6 +314,73 @@ mod tests {
         assert!(result.is_err());
     });
 
+    test_all_algos!(test_multiple_needles, |algo: Algorithm| {
+        let finder = Finder::with_needles(
+            Cursor::new(&b"xxfooxxbar"[..]),
+            vec![b"foo".to_vec(), b"bar".to_vec()],
+            Some(algo),
+        )
+        .unwrap();
+        let results: Vec<_> = finder.map(|r| r.unwrap()).collect();
+        assert_eq!(results, vec![(0, 2), (1, 7)]);
+
+        let finder = Finder::with_needles(
+            Cursor::new(&b"xxfooxxbar"[..]),
+            vec![b"foo".to_vec(), b"bar".to_vec()],
+            Some(algo),
+        )
+        .unwrap();
+        let results: Vec<_> = finder.positions_u64().map(|r| r.unwrap()).collect();
+        assert_eq!(results, vec![(0, 2u64), (1, 7)]);
+    });
+
+    test_all_algos!(
+        test_multiple_needles_across_buffer_boundary,
+        |algo: Algorithm| {
+            let mut haystack = vec![0; DEFAULT_BUF_SIZE - 2];
+            haystack.extend_from_slice(b"ab-longneedle");
+            let finder = Finder::with_needles(
+                Cursor::new(haystack),
+                vec![b"longneedle".to_vec(), b"b".to_vec(), b"ab".to_vec()],
+                Some(algo),
+            )
+            .unwrap();
+            let results: Vec<_> = finder.map(|r| r.unwrap()).collect();
+            assert_eq!(
+                results,
+                vec![
+                    (2, DEFAULT_BUF_SIZE - 2),
+                    (1, DEFAULT_BUF_SIZE - 1),
+                    (0, DEFAULT_BUF_SIZE + 1)
+                ]
+            );
+        }
+    );
+
+    #[test]
+    fn test_multiple_needles_rejects_empty() {
+        let result = Finder::with_needles(
+            Cursor::new(&b"test"[..]),
+            vec![b"te".to_vec(), vec![]],
+            None,
+        );
+        assert!(result.is_err());
+    }
+
+    test_all_algos!(test_bytes_consumed, |algo: Algorithm| {
+        let haystack = b"a test string with test";
+        let mut finder =
+            Finder::new(Cursor::new(&haystack[..]), b"test".to_vec(), Some(algo)).unwrap();
+        assert_eq!(finder.bytes_consumed(), 0);
+        assert_eq!(finder.next().unwrap().unwrap(), 2);
+        assert_eq!(finder.bytes_consumed(), 3);
+        assert_eq!(
+            finder.by_ref().map(|r| r.unwrap()).collect::<Vec<_>>(),
+            vec![19]
+        );
+        assert_eq!(finder.bytes_consumed(), haystack.len());
+    });
+
     #[test]
     fn test_empty_needle_naive() {
         let algo = Algorithm::Naive;
@@ -142,11 +417,9 @@ mod tests {
                 max_threads
             };
 
-            let mut per_thread_limit = if threads_to_use > 0 {
-                memory_limit / threads_to_use
-            } else {
-                memory_limit
-            };
+            let mut per_thread_limit = memory_limit
+                .checked_div(threads_to_use)
+                .unwrap_or(memory_limit);
             if per_thread_limit == 0 {
                 per_thread_limit = 1;
             }
@@ -174,17 +447,115 @@ mod tests {
             let naive_result = find_all(&haystack, &needle, Algorithm::Naive);
             let bmh_result = find_all(&haystack, &needle, Algorithm::Bmh);
             let kmp_result = find_all(&haystack, &needle, Algorithm::Kmp);
+            // Needles over SHIFT_AND_MAX_LEN take the naive fallback
+            let shift_and_result = find_all(&haystack, &needle, Algorithm::ShiftAnd);
             let simd_result = find_all(&haystack, &needle, Algorithm::Simd);
+            let auto_result = find_all(&haystack, &needle, Algorithm::Auto);
             #[cfg(target_arch = "x86_64")]
             let simdx86_64_result = find_all(&haystack, &needle, Algorithm::SimdX8664);
 
             // All results should be identical
             prop_assert_eq!(&naive_result, &bmh_result);
             prop_assert_eq!(&naive_result, &kmp_result);
+            prop_assert_eq!(&naive_result, &shift_and_result);
             prop_assert_eq!(&naive_result, &simd_result);
+            prop_assert_eq!(&naive_result, &auto_result);
             #[cfg(target_arch = "x86_64")]
             prop_assert_eq!(&naive_result, &simdx86_64_result);
         }
+
+        #[test]
+        fn chunked_reads_match_slice_search(
+            haystack in prop::collection::vec(0u8..4, 0..600),
+            needle in prop::collection::vec(0u8..4, 1..10),
+            chunks in prop::collection::vec(1usize..=7, 1..16),
+            extra_buffer in 0usize..16,
+        ) {
+            // Small buffers and a tiny alphabet exercise partial reads, tail copies and
+            // matches straddling buffer windows
+            let expected: Vec<usize> = crate::SliceFinder::new(&haystack, needle.clone())
+                .unwrap()
+                .find_all(Algorithm::Naive)
+                .collect();
+            for &algo in Algorithm::ALL {
+                let reader = ChunkedReader { data: &haystack, chunks: chunks.clone(), next: 0 };
+                let finder = Finder::with_buffer_size(
+                    reader,
+                    needle.clone(),
+                    needle.len() + extra_buffer,
+                    Some(algo),
+                )
+                .unwrap();
+                prop_assert_eq!(finder.collect_all().unwrap(), expected.clone(), "{}", algo);
+            }
+        }
+
+        #[test]
+        fn bmh_galil_find_all_matches_naive(
+            haystack in prop::collection::vec(0u8..3, 0..1000),
+            needle in prop::collection::vec(0u8..3, 1..12)
+        ) {
+            // A tiny alphabet makes periodic needles and dense overlapping matches common
+            prop_assert_eq!(
+                crate::find_all_in_slice(&haystack, &needle, Algorithm::Bmh),
+                find_all(&haystack, &needle, Algorithm::Naive)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_decode_odd_length_index() {
+        use crate::hex::{decode, FromHexError};
+
+        assert_eq!(decode("dead"), Ok(vec![0xde, 0xad]));
+        assert_eq!(decode("dea"), Err(FromHexError::OddLength { index: 2 }));
+    }
+
+    #[test]
+    fn test_hex_decode_needle() {
+        use crate::hex::{decode_needle, FromHexError};
+
+        assert_eq!(decode_needle("DEad"), Ok(vec![0xde, 0xad]));
+        assert_eq!(decode_needle(""), Err(FromHexError::EmptyNeedle));
+        assert_eq!(
+            FromHexError::EmptyNeedle.to_string(),
+            "hex needle decoded to zero bytes"
+        );
+        assert_eq!(
+            decode_needle("dea"),
+            Err(FromHexError::OddLength { index: 2 })
+        );
+        assert_eq!(
+            decode_needle("d"),
+            Err(FromHexError::OddLength { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_hex_decoder_chunks() {
+        use crate::hex::{FromHexError, HexDecoder};
+
+        let mut decoder = HexDecoder::new();
+        decoder.push(b"de").unwrap();
+        decoder.push(b"ad").unwrap();
+        assert_eq!(decoder.finish(), Ok(vec![0xde, 0xad]));
+
+        let mut decoder = HexDecoder::new();
+        decoder.push(b"dea").unwrap();
+        decoder.push(b"d").unwrap();
+        assert_eq!(decoder.finish(), Ok(vec![0xde, 0xad]));
+
+        let mut decoder = HexDecoder::new();
+        decoder.push(b"de").unwrap();
+        decoder.push(b"a").unwrap();
+        assert_eq!(decoder.finish(), Err(FromHexError::OddLength { index: 2 }));
+
+        let mut decoder = HexDecoder::new();
+        decoder.push(b"de").unwrap();
+        assert_eq!(
+            decoder.push(b"az"),
+            Err(FromHexError::InvalidHexCharacter { c: 'z', index: 3 })
+        );
     }
 
     #[test]
@@ -216,4 +587,1911 @@ mod tests {
         let positions2 = crate::find_in_file(temp_file.path(), needle, Algorithm::Naive).unwrap();
         assert_eq!(positions2, vec![0, 12]);
     }
+
+    #[test]
+    fn test_count_and_nth_in_file() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(b"hello world hello there hello")
+            .unwrap();
+        temp_file.flush().unwrap();
+
+        let count = crate::count_in_file(temp_file.path(), b"hello", Algorithm::Naive).unwrap();
+        assert_eq!(count, 3);
+
+        let nth = crate::find_nth_in_file(temp_file.path(), b"hello", 1, Algorithm::Naive);
+        assert_eq!(nth.unwrap(), Some(12));
+        let nth = crate::find_nth_in_file(temp_file.path(), b"hello", 10, Algorithm::Naive);
+        assert_eq!(nth.unwrap(), None);
+    }
+
+    #[test]
+    fn test_find_in_file_overlapping_modes() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"aaaa").unwrap();
+        temp_file.flush().unwrap();
+
+        let overlapping =
+            crate::find_in_file_with(temp_file.path(), b"aa", Algorithm::Naive, true).unwrap();
+        assert_eq!(overlapping, vec![0, 1, 2]);
+        let non_overlapping =
+            crate::find_in_file_with(temp_file.path(), b"aa", Algorithm::Naive, false).unwrap();
+        assert_eq!(non_overlapping, vec![0, 2]);
+        // Default helper keeps overlapping behavior
+        let default = crate::find_in_file(temp_file.path(), b"aa", Algorithm::Naive).unwrap();
+        assert_eq!(default, overlapping);
+
+        // A needle that can only match once is unaffected by the mode
+        for overlapping in [true, false] {
+            let whole =
+                crate::find_in_file_with(temp_file.path(), b"aaaa", Algorithm::Simd, overlapping)
+                    .unwrap();
+            assert_eq!(whole, vec![0]);
+        }
+    }
+
+    #[test]
+    fn test_mmap_finder_progress() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut haystack = vec![b'.'; 4 * 1024 * 1024];
+        haystack[10..15].copy_from_slice(b"hello");
+        let len = haystack.len();
+        haystack[len - 5..].copy_from_slice(b"hello");
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&haystack).unwrap();
+        temp_file.flush().unwrap();
+
+        let finder = MmapFinder::new(temp_file.path(), b"hello".to_vec()).unwrap();
+        let mut reported = Vec::new();
+        let positions = finder.find_all_with_progress(Algorithm::Simd, |pos| reported.push(pos));
+
+        assert_eq!(positions, vec![10, len - 5]);
+        assert!(!reported.is_empty());
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(*reported.last().unwrap(), len);
+    }
+
+    #[test]
+    fn test_algorithm_name_round_trip() {
+        for &algo in Algorithm::ALL {
+            assert_eq!(algo.to_string().parse::<Algorithm>(), Ok(algo));
+        }
+        assert_eq!("naive".parse::<Algorithm>(), Ok(Algorithm::Naive));
+        assert_eq!("BMH".parse::<Algorithm>(), Ok(Algorithm::Bmh));
+        assert_eq!(Algorithm::Kmp.to_string(), "kmp");
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!("simdx8664".parse::<Algorithm>(), Ok(Algorithm::SimdX8664));
+        assert!("boyer-moore".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn test_finder_getters() {
+        let finder = Finder::with_buffer_size(
+            Cursor::new(&b"test"[..]),
+            b"needle".to_vec(),
+            4096,
+            Some(Algorithm::Bmh),
+        )
+        .unwrap();
+        assert_eq!(finder.algorithm(), Algorithm::Bmh);
+        assert_eq!(finder.buffer_size(), 4096);
+        assert_eq!(finder.needle(), b"needle");
+
+        let finder = Finder::new(Cursor::new(&b"test"[..]), b"t".to_vec(), None).unwrap();
+        assert_eq!(finder.algorithm(), Algorithm::Naive);
+        assert_eq!(finder.buffer_size(), DEFAULT_BUF_SIZE);
+    }
+
+    #[test]
+    fn test_hex_decode_wildcard() {
+        use crate::hex::{decode_wildcard, FromHexError};
+
+        let pattern = decode_wildcard("de??beef").unwrap();
+        assert_eq!(pattern, vec![Some(0xde), None, Some(0xbe), Some(0xef)]);
+        assert_eq!(
+            crate::wildcard_search(&[0xde, 0x00, 0xbe, 0xef], &pattern),
+            Some(0)
+        );
+        assert_eq!(
+            crate::wildcard_search(&[0xde, 0x00, 0xbe, 0xee], &pattern),
+            None
+        );
+
+        assert_eq!(
+            decode_wildcard("de?f"),
+            Err(FromHexError::InvalidHexCharacter { c: '?', index: 2 })
+        );
+        assert_eq!(
+            decode_wildcard("de?"),
+            Err(FromHexError::OddLength { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_slice_finder() {
+        use crate::SliceFinder;
+
+        let haystack = b"hello world hello universe".to_vec();
+        let finder = SliceFinder::new(&haystack, b"hello".to_vec()).unwrap();
+
+        assert_eq!(finder.find_first(Algorithm::Naive), Some(0));
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_last(algo), Some(12));
+        }
+        let positions: Vec<usize> = finder.find_all(Algorithm::Naive).collect();
+        assert_eq!(positions, vec![0, 12]);
+        assert_eq!(finder.as_bytes(), &haystack[..]);
+
+        assert!(SliceFinder::new(&haystack, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_mmap_finder_rev() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"aaaa baab aaa").unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"aa".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            let mut forward: Vec<usize> = finder.find_all(algo).collect();
+            assert_eq!(forward, vec![0, 1, 2, 6, 10, 11]);
+            let backward: Vec<usize> = finder.find_all(algo).rev().collect();
+            forward.reverse();
+            assert_eq!(backward, forward);
+
+            // Both ends meet without reporting a match twice
+            let mut iter = finder.find_all(algo);
+            assert_eq!(iter.next(), Some(0));
+            assert_eq!(iter.next_back(), Some(11));
+            assert_eq!(iter.next_back(), Some(10));
+            assert_eq!(iter.next(), Some(1));
+            assert_eq!(iter.next(), Some(2));
+            assert_eq!(iter.next_back(), Some(6));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+
+            let mut forward: Vec<usize> = finder.find_all_with(algo, false).collect();
+            assert_eq!(forward, vec![0, 2, 6, 10]);
+            let backward: Vec<usize> = finder.find_all_with(algo, false).rev().collect();
+            forward.reverse();
+            assert_eq!(backward, forward);
+
+            let mut iter = finder.find_all_with(algo, false);
+            assert_eq!(iter.next(), Some(0));
+            assert_eq!(iter.next_back(), Some(10));
+            assert_eq!(iter.next(), Some(2));
+            assert_eq!(iter.next_back(), Some(6));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn test_rev_across_reverse_chunks() {
+        use crate::SliceFinder;
+
+        // The first reverse chunk is the last 4096 + 3 bytes, it starts in the middle of
+        // the match at 8187 so only the overlap with the next chunk finds it
+        let mut haystack = vec![b'.'; 3 * 4096];
+        let straddling = haystack.len() - (4096 + 3) - 2;
+        for start in [100, straddling] {
+            haystack[start..start + 4].copy_from_slice(b"abab");
+        }
+        let finder = SliceFinder::new(&haystack, b"abab".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            let mut forward: Vec<usize> = finder.find_all(algo).collect();
+            assert_eq!(forward, vec![100, straddling]);
+            let backward: Vec<usize> = finder.find_all(algo).rev().collect();
+            forward.reverse();
+            assert_eq!(backward, forward, "{algo:?}");
+            assert_eq!(finder.find_last(algo), Some(straddling));
+        }
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    #[should_panic(expected = "disagrees with naive_search")]
+    fn test_verify_trips_on_mismatch() {
+        crate::search::verify_against_naive("broken_search", b"abc", b"b", Some(0));
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_verify_accepts_simd_results() {
+        let haystack = b"the quick brown fox jumps over the lazy dog";
+        for needle in [&b"o"[..], b"ox", b"the", b"lazy dog", b"missing"] {
+            crate::search::verify_against_naive(
+                "simd_search",
+                haystack,
+                needle,
+                crate::simd_search(haystack, needle),
+            );
+        }
+    }
+
+    #[test]
+    fn test_collect_all() {
+        let finder = Finder::new(Cursor::new(b"abcabcab"), b"ab".to_vec(), None).unwrap();
+        assert_eq!(finder.collect_all().unwrap(), vec![0, 3, 6]);
+
+        let finder = Finder::new(Cursor::new(b"abc"), b"zz".to_vec(), None).unwrap();
+        assert_eq!(finder.collect_all().unwrap(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_collect_all_stops_on_error() {
+        let finder = Finder::new(FailingReader(b"ab..ab.."), b"ab".to_vec(), None).unwrap();
+        let err = finder.collect_all().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert_eq!(err.to_string(), "disk on fire");
+    }
+
+    #[test]
+    fn test_chunks() {
+        let haystack = b"ab.ab.ab.ab.ab";
+        let finder = Finder::new(Cursor::new(haystack), b"ab".to_vec(), None).unwrap();
+        let batches: Vec<Vec<usize>> = finder.chunks(2).map(Result::unwrap).collect();
+        assert_eq!(batches, vec![vec![0, 3], vec![6, 9], vec![12]]);
+
+        // A batch size far larger than the stream yields one short batch
+        for n in [1 << 40, usize::MAX] {
+            let finder = Finder::new(Cursor::new(haystack), b"ab".to_vec(), None).unwrap();
+            let batches: Vec<Vec<usize>> = finder.chunks(n).map(Result::unwrap).collect();
+            assert_eq!(batches, vec![vec![0, 3, 6, 9, 12]]);
+        }
+
+        let finder = Finder::new(Cursor::new(haystack), b"zz".to_vec(), None).unwrap();
+        assert_eq!(finder.chunks(2).count(), 0);
+
+        // Positions read before an error are yielded ahead of it
+        let finder = Finder::new(FailingReader(b"ab.ab.ab"), b"ab".to_vec(), None).unwrap();
+        let mut batches = finder.chunks(2);
+        assert_eq!(batches.next().unwrap().unwrap(), vec![0, 3]);
+        assert_eq!(batches.next().unwrap().unwrap(), vec![6]);
+        assert!(batches.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_mmap_finder_new_hex() {
+        use crate::{hex::FromHexError, MmapFinder, MmapFinderError};
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(&[0x00, 0xde, 0xad, 0xbe, 0xef])
+            .unwrap();
+        temp_file.flush().unwrap();
+
+        let finder = MmapFinder::new_hex(temp_file.path(), "dead").unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Naive).collect::<Vec<_>>(),
+            vec![1]
+        );
+        let finder = MmapFinder::new_hex(temp_file.path(), "BEEF").unwrap();
+        assert_eq!(finder.find_first(Algorithm::Simd), Some(3));
+
+        assert!(matches!(
+            MmapFinder::new_hex(temp_file.path(), "dea"),
+            Err(MmapFinderError::Hex(FromHexError::OddLength { .. }))
+        ));
+        assert!(matches!(
+            MmapFinder::new_hex(temp_file.path(), "zz"),
+            Err(MmapFinderError::Hex(
+                FromHexError::InvalidHexCharacter { .. }
+            ))
+        ));
+        assert!(matches!(
+            MmapFinder::new_hex(temp_file.path(), ""),
+            Err(MmapFinderError::EmptyNeedle)
+        ));
+    }
+
+    #[test]
+    fn test_new_rejects_needles_over_max_len() {
+        use crate::{FinderError, MAX_NEEDLE_LEN};
+
+        let haystack = vec![b'a'; 3 * MAX_NEEDLE_LEN];
+        let needle = vec![b'a'; MAX_NEEDLE_LEN];
+        let finder = Finder::new(Cursor::new(&haystack), needle, None).unwrap();
+        assert_eq!(finder.buffer_size(), DEFAULT_BUF_SIZE);
+        assert_eq!(finder.count(), 2 * MAX_NEEDLE_LEN + 1);
+
+        let needle = vec![b'a'; MAX_NEEDLE_LEN + 1];
+        assert!(matches!(
+            Finder::new(Cursor::new(&haystack), needle.clone(), None),
+            Err(FinderError::BufferTooSmall)
+        ));
+        assert!(matches!(
+            Finder::with_algorithm(Cursor::new(&haystack), needle, Algorithm::Bmh),
+            Err(FinderError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_with_buffer_size_for_large_needle() {
+        let needle: Vec<u8> = (0..20 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut haystack = vec![b'.'; 100 * 1024];
+        haystack.extend_from_slice(&needle);
+
+        for algo in [
+            Algorithm::Naive,
+            Algorithm::Bmh,
+            Algorithm::Kmp,
+            Algorithm::Simd,
+        ] {
+            let mut reader = SlowReader::new(&haystack, 3000);
+            let finder =
+                Finder::with_buffer_size(&mut reader, needle.clone(), 32 * 1024, Some(algo))
+                    .unwrap();
+            assert_eq!(finder.collect_all().unwrap(), vec![100 * 1024]);
+        }
+    }
+
+    #[test]
+    fn test_offset_to_line_col() {
+        use crate::lines::offset_to_line_col;
+
+        let data = b"first\nsecond line\r\n\nlast";
+        assert_eq!(offset_to_line_col(data, 0), (1, 1));
+        assert_eq!(offset_to_line_col(data, 4), (1, 5));
+        // The newline itself belongs to the line it ends
+        assert_eq!(offset_to_line_col(data, 5), (1, 6));
+        assert_eq!(offset_to_line_col(data, 6), (2, 1));
+        assert_eq!(offset_to_line_col(data, 13), (2, 8));
+        // CRLF: the carriage return is the last column of its line
+        assert_eq!(offset_to_line_col(data, 17), (2, 12));
+        assert_eq!(offset_to_line_col(data, 19), (3, 1));
+        // No trailing newline
+        assert_eq!(offset_to_line_col(data, 20), (4, 1));
+        assert_eq!(offset_to_line_col(data, 23), (4, 4));
+        assert_eq!(offset_to_line_col(data, data.len()), (4, 5));
+        assert_eq!(offset_to_line_col(b"", 0), (1, 1));
+    }
+
+    #[test]
+    fn test_kmp_finder_reuses_table_across_windows() {
+        let haystack = b"aabaabaaab".repeat(50);
+        let needle = b"aabaaab".to_vec();
+        let expected = find_all(&haystack, &needle, Algorithm::Naive);
+        assert!(!expected.is_empty());
+
+        for buffer_size in [needle.len(), 16, 64] {
+            let mut reader = SlowReader::new(&haystack, 5);
+            let finder = Finder::with_buffer_size(
+                &mut reader,
+                needle.clone(),
+                buffer_size,
+                Some(Algorithm::Kmp),
+            )
+            .unwrap();
+            assert_eq!(finder.collect_all().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_with_capacity_hint() {
+        let mut haystack = vec![b'.'; 2048];
+        haystack[2040..].copy_from_slice(b"needle!!");
+
+        let mut reader = SlowReader::new(&haystack, usize::MAX);
+        let mut finder =
+            Finder::with_capacity_hint(&mut reader, b"needle".to_vec(), 2048, None).unwrap();
+        assert_eq!(finder.buffer_size(), 2048);
+        assert_eq!(finder.next().unwrap().unwrap(), 2040);
+        drop(finder);
+        // The whole stream was read at once
+        assert_eq!(reader.bytes_read, 2048);
+
+        let finder = Finder::with_capacity_hint(Cursor::new(b"abc"), b"abc".to_vec(), 1, None);
+        assert_eq!(finder.unwrap().buffer_size(), 3);
+        let finder =
+            Finder::with_capacity_hint(Cursor::new(b"abc"), b"abc".to_vec(), usize::MAX, None);
+        assert_eq!(finder.unwrap().buffer_size(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_merged_ranges() {
+        fn ranges(haystack: &[u8], needle: &[u8], buffer_size: usize) -> Vec<(usize, usize)> {
+            let finder =
+                Finder::with_buffer_size(Cursor::new(haystack), needle.to_vec(), buffer_size, None)
+                    .unwrap();
+            finder.merged_ranges().map(|r| r.unwrap()).collect()
+        }
+
+        // Fully overlapping
+        assert_eq!(ranges(b"aaaaa", b"aa", DEFAULT_BUF_SIZE), vec![(0, 5)]);
+        // Partially overlapping runs separated by other bytes
+        assert_eq!(
+            ranges(b"abababxxabab.ab", b"abab", DEFAULT_BUF_SIZE),
+            vec![(0, 6), (8, 12)]
+        );
+        // Disjoint and touching matches stay separate
+        assert_eq!(
+            ranges(b"ab.abab", b"ab", DEFAULT_BUF_SIZE),
+            vec![(0, 2), (3, 5), (5, 7)]
+        );
+        assert_eq!(ranges(b"xyz", b"ab", DEFAULT_BUF_SIZE), vec![]);
+
+        // Runs spanning several buffer windows
+        let mut haystack = vec![b'a'; 50];
+        haystack.extend_from_slice(b"..");
+        haystack.extend(vec![b'a'; 7]);
+        for buffer_size in [3, 4, 16] {
+            assert_eq!(
+                ranges(&haystack, b"aaa", buffer_size),
+                vec![(0, 50), (52, 59)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_mmap_finder_shared_across_threads() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MmapFinder>();
+
+        let mut haystack = vec![b'.'; 256 * 1024];
+        for pos in [100, 4096, 200_000] {
+            haystack[pos..pos + 6].copy_from_slice(b"needle");
+        }
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&haystack).unwrap();
+        temp_file.flush().unwrap();
+
+        let finder = MmapFinder::new(temp_file.path(), b"needle".to_vec()).unwrap();
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let finder = &finder;
+                    let algo = Algorithm::ALL[i % Algorithm::ALL.len()];
+                    scope.spawn(move || {
+                        (
+                            finder.find_first(algo),
+                            finder.find_all(algo).collect::<Vec<_>>(),
+                        )
+                    })
+                })
+                .collect();
+            for handle in handles {
+                assert_eq!(
+                    handle.join().unwrap(),
+                    (Some(100), vec![100, 4096, 200_000])
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_mmap_finder_for_each_match() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use std::ops::ControlFlow;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"abaabababa.ab").unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"aba".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            let mut seen = Vec::new();
+            finder.for_each_match(algo, |pos| {
+                seen.push(pos);
+                ControlFlow::Continue(())
+            });
+            assert_eq!(seen, finder.find_all(algo).collect::<Vec<_>>());
+            assert_eq!(seen, vec![0, 3, 5, 7]);
+
+            let mut seen = Vec::new();
+            finder.for_each_match(algo, |pos| {
+                seen.push(pos);
+                if seen.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            assert_eq!(seen, vec![0, 3]);
+        }
+    }
+
+    #[test]
+    fn test_mmap_finder_find_from() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"hello world hello").unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"hello".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_from(0, algo), Some(0));
+            assert_eq!(finder.find_from(1, algo), Some(12));
+            assert_eq!(finder.find_from(12, algo), Some(12));
+            assert_eq!(finder.find_from(13, algo), None);
+            assert_eq!(finder.find_from(usize::MAX, algo), None);
+        }
+    }
+
+    #[test]
+    fn test_validate_needle() {
+        use crate::finder::validate_needle;
+        use crate::FinderError;
+
+        assert!(validate_needle(b"abc", 3).is_ok());
+        assert!(validate_needle(b"abc", usize::MAX).is_ok());
+        assert!(matches!(
+            validate_needle(b"", 3),
+            Err(FinderError::EmptyNeedle)
+        ));
+        assert!(matches!(
+            validate_needle(b"abcd", 3),
+            Err(FinderError::BufferTooSmall)
+        ));
+
+        let finder = Finder::with_buffer_size(Cursor::new(b"abcd"), b"abcd".to_vec(), 3, None);
+        assert!(matches!(finder, Err(FinderError::BufferTooSmall)));
+        let finder = Finder::with_capacity_hint(Cursor::new(b"abcd"), Vec::new(), 3, None);
+        assert!(matches!(finder, Err(FinderError::EmptyNeedle)));
+    }
+
+    #[test]
+    fn test_positions_into_reuses_vec() {
+        let mut positions = vec![42, 43, 44, 45, 46];
+
+        let finder = Finder::new(Cursor::new(b"ab.ab.ab"), b"ab".to_vec(), None).unwrap();
+        finder.positions_into(&mut positions).unwrap();
+        assert_eq!(positions, vec![0, 3, 6]);
+        let capacity = positions.capacity();
+
+        let finder = Finder::new(Cursor::new(b"..ab"), b"ab".to_vec(), None).unwrap();
+        finder.positions_into(&mut positions).unwrap();
+        assert_eq!(positions, vec![2]);
+        assert_eq!(positions.capacity(), capacity);
+
+        let finder = Finder::new(Cursor::new(b"...."), b"ab".to_vec(), None).unwrap();
+        finder.positions_into(&mut positions).unwrap();
+        assert!(positions.is_empty());
+    }
+
+    #[test]
+    fn test_empty_needle_policy() {
+        use crate::{EmptyNeedlePolicy, FinderError};
+
+        let finder = Finder::with_empty_needle_policy(
+            Cursor::new(b"abc"),
+            Vec::new(),
+            None,
+            EmptyNeedlePolicy::Error,
+        );
+        assert!(matches!(finder, Err(FinderError::EmptyNeedle)));
+
+        let finder = Finder::with_empty_needle_policy(
+            Cursor::new(b"abc"),
+            Vec::new(),
+            None,
+            EmptyNeedlePolicy::MatchAll,
+        );
+        assert_eq!(finder.unwrap().collect_all().unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(
+            "abc".match_indices("").map(|(i, _)| i).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+
+        let finder = Finder::with_empty_needle_policy(
+            Cursor::new(b""),
+            Vec::new(),
+            None,
+            EmptyNeedlePolicy::MatchAll,
+        );
+        assert_eq!(finder.unwrap().collect_all().unwrap(), vec![0]);
+
+        // Positions stay contiguous across short reads
+        let haystack = [b'x'; 20];
+        let mut reader = SlowReader::new(&haystack, 3);
+        let finder = Finder::with_empty_needle_policy(
+            &mut reader,
+            Vec::new(),
+            None,
+            EmptyNeedlePolicy::MatchAll,
+        );
+        assert_eq!(
+            finder.unwrap().collect_all().unwrap(),
+            (0..=20).collect::<Vec<_>>()
+        );
+
+        // Non-empty needles are unaffected by the policy
+        let finder = Finder::with_empty_needle_policy(
+            Cursor::new(b"abcab"),
+            b"ab".to_vec(),
+            None,
+            EmptyNeedlePolicy::MatchAll,
+        );
+        assert_eq!(finder.unwrap().collect_all().unwrap(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_algo_map_first_match_wins() {
+        use crate::config::AlgoMap;
+        use std::path::Path;
+
+        let map: AlgoMap = "*.log=kmp,app.*=simd".parse().unwrap();
+        assert_eq!(map.get_for_path(Path::new("app.log")), Some(Algorithm::Kmp));
+        assert_eq!(
+            map.get_for_path(Path::new("app.txt")),
+            Some(Algorithm::Simd)
+        );
+
+        let map: AlgoMap = "app.*=simd,*.log=kmp,*.log=naive".parse().unwrap();
+        assert_eq!(
+            map.get_for_path(Path::new("logs/app.log")),
+            Some(Algorithm::Simd)
+        );
+        assert_eq!(map.get_for_path(Path::new("db.log")), Some(Algorithm::Kmp));
+    }
+
+    #[test]
+    fn test_algo_map_parse() {
+        use crate::config::{AlgoMap, ParseAlgoMapError};
+        use std::path::Path;
+
+        let map: AlgoMap = "*.txt=naive".parse().unwrap();
+        assert_eq!(
+            map.get_for_path(Path::new("notes.txt")),
+            Some(Algorithm::Naive)
+        );
+        assert_eq!(
+            map.get_for_path(Path::new("dir/notes.txt")),
+            Some(Algorithm::Naive)
+        );
+        assert_eq!(map.get_for_path(Path::new("image.bin")), None);
+
+        let map: AlgoMap = "*.txt=naive, data/*.bin = SIMD".parse().unwrap();
+        assert_eq!(
+            map.get_for_path(Path::new("data/a.bin")),
+            Some(Algorithm::Simd)
+        );
+        assert_eq!(map.get_for_path(Path::new("other/a.bin")), None);
+
+        assert!(matches!(
+            "*.txt".parse::<AlgoMap>(),
+            Err(ParseAlgoMapError::InvalidMapping(_))
+        ));
+        assert!(matches!(
+            "[.txt=naive".parse::<AlgoMap>(),
+            Err(ParseAlgoMapError::InvalidGlob(_))
+        ));
+        assert!(matches!(
+            "*.txt=quick".parse::<AlgoMap>(),
+            Err(ParseAlgoMapError::InvalidAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_algo_list_parse() {
+        use crate::config::AlgoList;
+
+        assert_eq!(
+            "naive, bmh,simd".parse::<AlgoList>(),
+            Ok(AlgoList(vec![
+                Algorithm::Naive,
+                Algorithm::Bmh,
+                Algorithm::Simd
+            ]))
+        );
+        assert!("naive,quick".parse::<AlgoList>().is_err());
+    }
+
+    #[test]
+    fn test_mmap_finder_len_hint() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"abababa.ab.ba").unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"aba".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.len_hint(algo), finder.find_all(algo).count());
+            assert_eq!(finder.len_hint(algo), 3);
+            assert_eq!(finder.count(algo), 3);
+        }
+    }
+
+    #[test]
+    fn test_encode_needle() {
+        use crate::encoding::{encode_needle, Encoding};
+
+        assert_eq!(encode_needle("AB", Encoding::Utf8), b"AB".to_vec());
+        assert_eq!(
+            encode_needle("AB", Encoding::Utf16Le),
+            vec![0x41, 0x00, 0x42, 0x00]
+        );
+        assert_eq!(
+            encode_needle("AB", Encoding::Utf16Be),
+            vec![0x00, 0x41, 0x00, 0x42]
+        );
+        assert_eq!(encode_needle("é", Encoding::Utf16Le), vec![0xe9, 0x00]);
+        assert_eq!("UTF16LE".parse(), Ok(Encoding::Utf16Le));
+        assert!("latin1".parse::<Encoding>().is_err());
+
+        // "xAB" stored as UTF-16LE, as in a Windows binary
+        let mut haystack = vec![0xff, 0x00, 0x41];
+        haystack.extend_from_slice(&[0x78, 0x00, 0x41, 0x00, 0x42, 0x00]);
+        let needle = encode_needle("AB", Encoding::Utf16Le);
+        assert_eq!(find_all(&haystack, &needle, Algorithm::Simd), vec![5]);
+    }
+
+    #[test]
+    fn test_with_base_offset() {
+        let file = b"header..ab...ab";
+        let window = &file[7..];
+
+        let mut finder =
+            Finder::with_base_offset(Cursor::new(window), b"ab".to_vec(), 1000, None).unwrap();
+        assert_eq!(finder.bytes_consumed(), 1000);
+        assert_eq!(finder.next().unwrap().unwrap(), 1001);
+        assert_eq!(finder.next().unwrap().unwrap(), 1006);
+        assert!(finder.next().is_none());
+        assert_eq!(finder.bytes_consumed(), 1000 + window.len());
+
+        let finder =
+            Finder::with_base_offset(Cursor::new(window), b"ab".to_vec(), 7, None).unwrap();
+        assert_eq!(
+            finder.collect_all().unwrap(),
+            find_all(file, b"ab", Algorithm::Naive)
+        );
+    }
+
+    #[test]
+    fn test_split_alternatives() {
+        use crate::config::split_alternatives;
+
+        assert_eq!(
+            split_alternatives(b"foo|bar|baz"),
+            vec![b"foo".to_vec(), b"bar".to_vec(), b"baz".to_vec()]
+        );
+        assert_eq!(
+            split_alternatives(br"a\|b|c\\|d\x"),
+            vec![b"a|b".to_vec(), br"c\".to_vec(), br"d\x".to_vec()]
+        );
+        assert_eq!(split_alternatives(b"foo"), vec![b"foo".to_vec()]);
+        assert_eq!(split_alternatives(b"foo|"), vec![b"foo".to_vec(), vec![]]);
+
+        // End to end through the multi-needle finder
+        let needles = split_alternatives(b"foo|bar|baz");
+        let finder =
+            Finder::with_needles(Cursor::new(b"xx bar yy"), needles.clone(), None).unwrap();
+        let found: Vec<_> = finder
+            .map(|r| r.unwrap())
+            .map(|(idx, pos)| (pos, needles[idx].as_slice()))
+            .collect();
+        assert_eq!(found, vec![(3, &b"bar"[..])]);
+    }
+
+    #[test]
+    fn test_finder_error_into_io_error() {
+        use crate::FinderError;
+        use std::io::{self, ErrorKind};
+
+        fn count_matches(haystack: &[u8], needle: &[u8]) -> io::Result<usize> {
+            let finder = Finder::new(Cursor::new(haystack), needle.to_vec(), None)?;
+            Ok(finder.collect_all()?.len())
+        }
+
+        assert_eq!(count_matches(b"abab", b"ab").unwrap(), 2);
+        let err = count_matches(b"abab", b"").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "Needle is empty");
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<FinderError>()),
+            Some(FinderError::EmptyNeedle)
+        ));
+
+        let err = io::Error::from(FinderError::BufferTooSmall);
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "Needle is longer than the buffer");
+    }
+
+    #[test]
+    fn test_context_finder() {
+        use crate::ContextMatch;
+
+        fn matches(
+            haystack: &[u8],
+            needle: &[u8],
+            context: usize,
+            chunk: usize,
+        ) -> Vec<ContextMatch> {
+            let mut reader = SlowReader::new(haystack, chunk);
+            let finder = Finder::with_context(&mut reader, needle.to_vec(), context, None).unwrap();
+            finder.map(|r| r.unwrap()).collect()
+        }
+
+        let m = |offset, before: &[u8], after: &[u8]| ContextMatch {
+            offset,
+            before: before.to_vec(),
+            after: after.to_vec(),
+        };
+
+        for chunk in [1, 2, 5, usize::MAX] {
+            // Fewer bytes than requested at the start and end of the stream
+            assert_eq!(
+                matches(b"ab..........ab", b"ab", 3, chunk),
+                vec![m(0, b"", b"..."), m(12, b"...", b"")]
+            );
+            // Back-to-back matches with overlapping contexts
+            assert_eq!(
+                matches(b"xxab.ab.abyy", b"ab", 2, chunk),
+                vec![m(2, b"xx", b".a"), m(5, b"b.", b".a"), m(8, b"b.", b"yy")]
+            );
+            assert_eq!(
+                matches(b"aaaa", b"aa", 1, chunk),
+                vec![m(0, b"", b"a"), m(1, b"a", b"a"), m(2, b"a", b"")]
+            );
+            assert_eq!(matches(b"no match here", b"ab", 3, chunk), vec![]);
+        }
+
+        // Matches far apart in a stream larger than the read buffer
+        let mut haystack = vec![b'.'; 3 * DEFAULT_BUF_SIZE];
+        haystack[100..104].copy_from_slice(b"<ab>");
+        haystack[2 * DEFAULT_BUF_SIZE..2 * DEFAULT_BUF_SIZE + 4].copy_from_slice(b"<ab>");
+        let found = matches(&haystack, b"ab", 1, 1000);
+        assert_eq!(
+            found,
+            vec![
+                m(101, b"<", b">"),
+                m(2 * DEFAULT_BUF_SIZE as u64 + 1, b"<", b">")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_finder_single_byte_needle() {
+        use crate::SliceFinder;
+
+        let finder = SliceFinder::new(b"a.a.a.a", b"a".to_vec()).unwrap();
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_all(algo).collect::<Vec<_>>(), vec![0, 2, 4, 6]);
+            assert_eq!(
+                finder.find_all_with(algo, false).collect::<Vec<_>>(),
+                vec![0, 2, 4, 6]
+            );
+            assert_eq!(
+                finder.find_all(algo).rev().collect::<Vec<_>>(),
+                vec![6, 4, 2, 0]
+            );
+        }
+
+        let mut haystack = vec![b'.'; 1000];
+        for i in [0, 63, 64, 500, 999] {
+            haystack[i] = b'a';
+        }
+        let finder = SliceFinder::new(&haystack, b"a".to_vec()).unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Naive).collect::<Vec<_>>(),
+            crate::find_all_in_slice(&haystack, b"a", Algorithm::Naive)
+        );
+    }
+
+    #[test]
+    fn test_finder_with_custom_search_fn() {
+        use crate::{naive_search, SearchFn};
+
+        let haystack = b"abcabcab".repeat(100);
+        let expected = find_all(&haystack, b"cab", Algorithm::Naive);
+
+        let custom: SearchFn = naive_search;
+        for chunk in [1, 7, usize::MAX] {
+            let mut reader = SlowReader::new(&haystack, chunk);
+            let finder = Finder::with_custom(&mut reader, b"cab".to_vec(), custom).unwrap();
+            assert_eq!(finder.collect_all().unwrap(), expected);
+        }
+
+        // The custom function really replaces the algorithm
+        fn never(_: &[u8], _: &[u8]) -> Option<usize> {
+            None
+        }
+        let finder = Finder::with_custom(Cursor::new(&haystack), b"cab".to_vec(), never).unwrap();
+        assert_eq!(finder.collect_all().unwrap(), Vec::<usize>::new());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_finder_checksum() {
+        let mut haystack: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+        haystack[30_000..30_006].copy_from_slice(b"MARKER");
+        haystack[45_000..45_006].copy_from_slice(b"MARKER");
+
+        for chunk in [1, 1000, usize::MAX] {
+            let mut reader = SlowReader::new(&haystack, chunk);
+            let mut finder =
+                Finder::with_checksum(&mut reader, b"MARKER".to_vec(), Some(Algorithm::Simd))
+                    .unwrap();
+            assert_eq!(finder.checksum_so_far(), crc32fast::hash(b""));
+            assert_eq!(finder.next().unwrap().unwrap(), 30_000);
+            assert_eq!(
+                finder.checksum_so_far(),
+                crc32fast::hash(&haystack[..30_000])
+            );
+            assert_eq!(finder.next().unwrap().unwrap(), 45_000);
+            assert_eq!(
+                finder.checksum_so_far(),
+                crc32fast::hash(&haystack[..45_000])
+            );
+            assert!(finder.next().is_none());
+            assert_eq!(finder.checksum_so_far(), crc32fast::hash(&haystack));
+        }
+
+        // Skipped bytes are still part of the checksum
+        let mut finder =
+            Finder::with_checksum(Cursor::new(&haystack), b"MARKER".to_vec(), None).unwrap();
+        finder.skip_bytes(40_000).unwrap();
+        assert_eq!(finder.next().unwrap().unwrap(), 45_000);
+        assert!(finder.next().is_none());
+        assert_eq!(finder.checksum_so_far(), crc32fast::hash(&haystack));
+
+        let finder = Finder::new(Cursor::new(&haystack), b"MARKER".to_vec(), None).unwrap();
+        assert_eq!(finder.checksum_so_far(), 0);
+    }
+
+    #[test]
+    fn test_mmap_finder_new_populated() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut haystack = vec![b'.'; 1024 * 1024];
+        for pos in [0, 4090, 4096, 700_000, haystack.len() - 6] {
+            haystack[pos..pos + 6].copy_from_slice(b"needle");
+        }
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&haystack).unwrap();
+        temp_file.flush().unwrap();
+
+        let normal = MmapFinder::new(temp_file.path(), b"needle".to_vec()).unwrap();
+        let populated = MmapFinder::new_populated(temp_file.path(), b"needle".to_vec()).unwrap();
+        assert_eq!(populated.as_bytes(), normal.as_bytes());
+        for &algo in Algorithm::ALL {
+            assert_eq!(
+                populated.find_all(algo).collect::<Vec<_>>(),
+                normal.find_all(algo).collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(populated.count(Algorithm::Simd), 5);
+    }
+
+    #[test]
+    fn test_mmap_finder_find_all_slices() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"abcabcab abc").unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"abc".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            let slices: Vec<&[u8]> = finder.find_all_slices(algo).collect();
+            assert_eq!(slices.len(), finder.count(algo));
+            assert_eq!(slices.len(), 3);
+            assert!(slices.iter().all(|slice| *slice == b"abc"));
+        }
+    }
+
+    /// Reader producing `zeros` zero bytes followed by `tail`, without allocating them
+    struct HugeReader {
+        zeros: u64,
+        tail: &'static [u8],
+    }
+
+    impl std::io::Read for HugeReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.zeros > 0 {
+                let n = (buf.len() as u64).min(self.zeros) as usize;
+                buf[..n].fill(0);
+                self.zeros -= n as u64;
+                return Ok(n);
+            }
+            let n = buf.len().min(self.tail.len());
+            buf[..n].copy_from_slice(&self.tail[..n]);
+            self.tail = &self.tail[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        target_pointer_width = "64",
+        ignore = "offsets only overflow usize on 32-bit targets, and reading 4GB is slow"
+    )]
+    fn test_positions_u64_past_4gb() {
+        let zeros = u64::from(u32::MAX) + 10;
+        let reader = HugeReader {
+            zeros,
+            tail: b"needle",
+        };
+        let finder =
+            Finder::with_buffer_size(reader, b"needle".to_vec(), 1 << 20, Some(Algorithm::Simd))
+                .unwrap();
+        let positions: Vec<u64> = finder.positions_u64().collect::<Result<_, _>>().unwrap();
+        assert_eq!(positions, vec![zeros]);
+
+        let reader = HugeReader {
+            zeros,
+            tail: b"needle",
+        };
+        let mut finder =
+            Finder::with_buffer_size(reader, b"needle".to_vec(), 1 << 20, Some(Algorithm::Simd))
+                .unwrap();
+        match finder.next() {
+            Some(Ok(pos)) => assert_eq!(pos as u64, zeros),
+            Some(Err(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+            None => panic!("match past 4GB not reported"),
+        }
+
+        let reader = HugeReader {
+            zeros,
+            tail: b"needle",
+        };
+        let finder = Finder::with_needles(
+            reader,
+            vec![b"nee".to_vec(), b"dle".to_vec()],
+            Some(Algorithm::Simd),
+        )
+        .unwrap();
+        let positions: Vec<(usize, u64)> =
+            finder.positions_u64().collect::<Result<_, _>>().unwrap();
+        assert_eq!(positions, vec![(0, zeros), (1, zeros + 3)]);
+
+        let reader = HugeReader {
+            zeros,
+            tail: b"needle",
+        };
+        let finder =
+            Finder::with_context(reader, b"needle".to_vec(), 2, Some(Algorithm::Simd)).unwrap();
+        let matches: Vec<_> = finder.collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            matches,
+            vec![crate::ContextMatch {
+                offset: zeros,
+                before: vec![0, 0],
+                after: vec![],
+            }]
+        );
+    }
+
+    /// Reader counting how many bytes each `read` call asked for
+    struct RecordingReader<'a> {
+        data: &'a [u8],
+        max_request: usize,
+    }
+
+    impl std::io::Read for RecordingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.max_request = self.max_request.max(buf.len());
+            let n = buf.len().min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_chunk_caps_reads() {
+        let haystack = b"one needle, two needles and a needle at the end: needle";
+        let expected = vec![4, 16, 30, 49];
+
+        for &algo in Algorithm::ALL {
+            for read_chunk in [Some(0), Some(1), Some(3), None] {
+                let mut reader = RecordingReader {
+                    data: haystack,
+                    max_request: 0,
+                };
+                let finder = Finder::with_read_chunk(
+                    &mut reader,
+                    b"needle".to_vec(),
+                    16,
+                    read_chunk,
+                    Some(algo),
+                )
+                .unwrap();
+                assert_eq!(
+                    finder.collect_all().unwrap(),
+                    expected,
+                    "{algo} {read_chunk:?}"
+                );
+                if let Some(chunk) = read_chunk {
+                    assert_eq!(reader.max_request, chunk.max(1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "debug")]
+    fn test_positions_with_algo_reports_resolved_algorithm() {
+        let haystack = b"a,b,c";
+
+        let finder =
+            Finder::with_algorithm(Cursor::new(&haystack[..]), b",".to_vec(), Algorithm::Auto)
+                .unwrap();
+        let found: Vec<(usize, Algorithm)> = finder
+            .positions_with_algo()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(found, vec![(1, Algorithm::Simd), (3, Algorithm::Simd)]);
+
+        let finder =
+            Finder::with_algorithm(Cursor::new(&haystack[..]), b",".to_vec(), Algorithm::Kmp)
+                .unwrap();
+        let found: Vec<(usize, Algorithm)> = finder
+            .positions_with_algo()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(found, vec![(1, Algorithm::Kmp), (3, Algorithm::Kmp)]);
+    }
+
+    #[test]
+    fn test_finder_skip_bytes() {
+        for &algo in Algorithm::ALL {
+            let mut finder =
+                Finder::with_algorithm(Cursor::new(&b"xxxxxhello"[..]), b"hello".to_vec(), algo)
+                    .unwrap();
+            finder.skip_bytes(5).unwrap();
+            assert_eq!(finder.bytes_consumed(), 5);
+            assert_eq!(finder.collect_all().unwrap(), vec![5]);
+
+            // Skipped matches are not reported, even across buffer refills and short reads
+            let haystack = b"ab ab ab ab ab ab ab ab";
+            let reader = ChunkedReader {
+                data: haystack,
+                chunks: vec![3, 1, 4],
+                next: 0,
+            };
+            let mut finder =
+                Finder::with_buffer_size(reader, b"ab".to_vec(), 4, Some(algo)).unwrap();
+            assert_eq!(finder.next().unwrap().unwrap(), 0);
+            finder.skip_bytes(10).unwrap();
+            assert_eq!(finder.bytes_consumed(), 11);
+            assert_eq!(finder.collect_all().unwrap(), vec![12, 15, 18, 21]);
+
+            // Skipping past the end leaves nothing to find
+            let mut finder =
+                Finder::with_algorithm(Cursor::new(&b"hello"[..]), b"hello".to_vec(), algo)
+                    .unwrap();
+            finder.skip_bytes(100).unwrap();
+            assert_eq!(finder.next().map(|pos| pos.unwrap()), None);
+        }
+    }
+
+    #[test]
+    fn test_matches_with_window() {
+        let haystack = b"key=1;key=22;key=333";
+        for &algo in Algorithm::ALL {
+            let finder =
+                Finder::with_algorithm(Cursor::new(&haystack[..]), b";key=".to_vec(), algo)
+                    .unwrap();
+            let found: Vec<(usize, Vec<u8>)> = finder
+                .matches_with_window(2)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(
+                found,
+                vec![(5, b"=1;key=22".to_vec()), (12, b"22;key=33".to_vec())]
+            );
+
+            // Windows are cut at the start and end of the stream
+            let finder =
+                Finder::with_algorithm(Cursor::new(&haystack[..]), b"key=".to_vec(), algo).unwrap();
+            let windows: Vec<Vec<u8>> = finder
+                .matches_with_window(100)
+                .map(|found| found.unwrap().1)
+                .collect();
+            assert_eq!(windows, vec![haystack.to_vec(); 3]);
+        }
+
+        // A match right after a buffer refill only sees the carried bytes before it
+        let finder =
+            Finder::with_buffer_size(Cursor::new(&haystack[..]), b"key=".to_vec(), 8, None)
+                .unwrap();
+        for found in finder.matches_with_window(3) {
+            let (pos, window) = found.unwrap();
+            assert!(window.windows(4).any(|w| w == b"key="), "{pos} {window:?}");
+            assert!(window.len() <= 4 + 2 * 3);
+        }
+    }
+
+    #[test]
+    fn test_mmap_finder_case_insensitive() {
+        use crate::MmapFinder;
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(b"say hello, HeLLo and h\xc9llo")
+            .unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"HELLO".to_vec()).unwrap();
+
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_first_ci(algo), Some(4), "{algo}");
+            assert_eq!(finder.find_all_ci(algo).collect::<Vec<_>>(), vec![4, 11]);
+            assert_eq!(finder.find_first(algo), None);
+        }
+
+        // Needles too long for Shift-And take the naive path
+        let needle = b"HELLO ".repeat(20);
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(&[&b"x "[..], &b"hello ".repeat(20)].concat())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), needle).unwrap();
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_all_ci(algo).collect::<Vec<_>>(), vec![2]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_matches_serializable_round_trip() {
+        use crate::Match;
+
+        let finder = Finder::new(Cursor::new(&b"ab ab ab"[..]), b"ab".to_vec(), None).unwrap();
+        let matches: Vec<Match> = finder
+            .matches_serializable()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                Match { offset: 0, len: 2 },
+                Match { offset: 3, len: 2 },
+                Match { offset: 6, len: 2 },
+            ]
+        );
+
+        let json = serde_json::to_string(&matches).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"offset":0,"len":2},{"offset":3,"len":2},{"offset":6,"len":2}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<Match>>(&json).unwrap(), matches);
+    }
+
+    #[test]
+    fn test_prepared_tables_match_fresh_searches() {
+        use crate::SliceFinder;
+        use std::ops::ControlFlow;
+
+        // Dense, overlapping and periodic matches, every search after the first reuses
+        // the tables
+        let haystack = b"abaabaabaab abab aab".repeat(50);
+        for needle in [&b"abaab"[..], b"ab", b"a", b"aab aab"] {
+            let expected = crate::find_all_in_slice(&haystack, needle, Algorithm::Naive);
+            for &algo in Algorithm::ALL {
+                let finder = SliceFinder::new(&haystack, needle.to_vec()).unwrap();
+                let found: Vec<usize> = finder.find_all(algo).collect();
+                assert_eq!(found, expected, "{algo} {needle:?}");
+
+                let streamed = Finder::with_buffer_size(
+                    Cursor::new(&haystack),
+                    needle.to_vec(),
+                    64,
+                    Some(algo),
+                )
+                .unwrap()
+                .collect_all()
+                .unwrap();
+                assert_eq!(streamed, expected, "{algo} {needle:?}");
+            }
+        }
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, &haystack).unwrap();
+        let finder = crate::MmapFinder::new(temp_file.path(), b"abaab".to_vec()).unwrap();
+        let expected = crate::find_all_in_slice(&haystack, b"abaab", Algorithm::Naive);
+        for &algo in Algorithm::ALL {
+            let mut found = Vec::new();
+            finder.for_each_match(algo, |pos| {
+                found.push(pos);
+                ControlFlow::Continue(())
+            });
+            assert_eq!(found, expected);
+            assert_eq!(finder.find_all_with_progress(algo, |_| {}), expected);
+        }
+    }
+
+    #[test]
+    fn test_utf8_aligned_skips_matches_inside_codepoints() {
+        // "é" is encoded as [0xc3, 0xa9], neither byte alone is a char
+        let haystack = "café, né en été".as_bytes();
+        let cases: [(&[u8], Vec<usize>); 4] = [
+            (&[0xa9], vec![]),
+            (&[0xc3], vec![]),
+            (&[0xa9, b','], vec![]),
+            ("é".as_bytes(), vec![3, 8, 14, 17]),
+        ];
+        for (needle, expected) in cases {
+            for &algo in Algorithm::ALL {
+                let finder =
+                    Finder::utf8_aligned(Cursor::new(haystack), needle.to_vec(), Some(algo))
+                        .unwrap();
+                assert_eq!(finder.collect_all().unwrap(), expected, "{algo} {needle:?}");
+
+                // One byte per read leaves every match at the end of the buffered data
+                // until the next read
+                let mut reader = SlowReader::new(haystack, 1);
+                let finder =
+                    Finder::utf8_aligned(&mut reader, needle.to_vec(), Some(algo)).unwrap();
+                assert_eq!(finder.collect_all().unwrap(), expected, "{algo} {needle:?}");
+            }
+        }
+
+        // Plain finders still report raw byte matches
+        let finder = Finder::new(Cursor::new(haystack), vec![0xa9], None).unwrap();
+        assert_eq!(finder.collect_all().unwrap(), vec![4, 9, 15, 18]);
+    }
+
+    #[test]
+    fn test_find_last_seek() {
+        let haystack = b"needle hay needle hay hay needle hay";
+        let finder = Finder::new_seek(Cursor::new(haystack), b"needle".to_vec(), None).unwrap();
+        assert_eq!(finder.find_last_seek().unwrap(), Some(26));
+
+        let finder = Finder::new_seek(Cursor::new(haystack), b"straw".to_vec(), None).unwrap();
+        assert_eq!(finder.find_last_seek().unwrap(), None);
+
+        // Small buffers put matches across chunk boundaries, the reader position is ignored
+        let haystack = b"abcab cabca bcabc abcxx".repeat(20);
+        let expected = crate::find_all_in_slice(&haystack, b"abca", Algorithm::Naive);
+        for &algo in Algorithm::ALL {
+            for buffer_size in [4, 5, 7, 64] {
+                let mut cursor = Cursor::new(&haystack);
+                cursor.set_position(10);
+                let finder =
+                    Finder::with_buffer_size(cursor, b"abca".to_vec(), buffer_size, Some(algo))
+                        .unwrap();
+                assert_eq!(
+                    finder.find_last_seek().unwrap(),
+                    expected.last().map(|&pos| pos as u64),
+                    "{algo} {buffer_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        // The only match starts at offset 6, past the limit
+        let finder =
+            Finder::with_byte_limit(Cursor::new(b"abcdefneedle"), b"needle".to_vec(), 5, None)
+                .unwrap();
+        assert_eq!(finder.collect_all().unwrap(), Vec::<usize>::new());
+
+        // A match straddling the limit is reported, reading stops right after it
+        let haystack = b"xxxneedle needle";
+        for &algo in Algorithm::ALL {
+            let mut reader = SlowReader::new(haystack, 2);
+            let finder =
+                Finder::with_byte_limit(&mut reader, b"needle".to_vec(), 5, Some(algo)).unwrap();
+            assert_eq!(finder.collect_all().unwrap(), vec![3], "{algo}");
+            assert_eq!(reader.bytes_read, 10, "{algo}");
+        }
+
+        // Endless streams end the search at the limit
+        let finder =
+            Finder::with_byte_limit(std::io::repeat(b'a'), b"ab".to_vec(), 100_000, None).unwrap();
+        assert_eq!(finder.collect_all().unwrap(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_mmap_finder_set_needle() {
+        use crate::{MmapFinder, MmapFinderError};
+        use std::io::Write;
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"foo bar foo baz bar").unwrap();
+        temp_file.flush().unwrap();
+
+        let mut finder = MmapFinder::new(temp_file.path(), b"foo".to_vec()).unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Bmh).collect::<Vec<_>>(),
+            vec![0, 8]
+        );
+
+        finder.set_needle(b"bar".to_vec()).unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Bmh).collect::<Vec<_>>(),
+            vec![4, 16]
+        );
+        assert_eq!(finder.find_first(Algorithm::Simd), Some(4));
+
+        // A rejected needle leaves the previous one in place
+        assert!(matches!(
+            finder.set_needle(Vec::new()),
+            Err(MmapFinderError::EmptyNeedle)
+        ));
+        assert_eq!(finder.find_last(Algorithm::Naive), Some(16));
+    }
+
+    #[test]
+    fn test_differential_check() {
+        use crate::differential_check;
+
+        let cases: [(&[u8], &[u8]); 6] = [
+            (b"hello world", b"world"),
+            (b"hello world", b"xyz"),
+            (b"abcabc", b"abc"),
+            (b"aaab", b"aab"),
+            (b"", b"a"),
+            (b"abc", b""),
+        ];
+        assert_eq!(differential_check(crate::bmh_search, &cases), Ok(()));
+
+        // Misses matches at the very start of the haystack
+        let broken: crate::SearchFn =
+            |haystack, needle| crate::naive_search(haystack.get(1..)?, needle).map(|i| i + 1);
+        let err = differential_check(broken, &cases).unwrap_err();
+        assert_eq!(
+            err,
+            "case 2: found Some(3), naive_search found Some(0) for needle \"abc\" in haystack \"abcabc\""
+        );
+    }
+
+    #[test]
+    fn test_find_in_file_range() {
+        use crate::find_in_file_range;
+        use std::io::{Seek, Write};
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"ab--ab--ab--ab--ab").unwrap();
+        temp_file.flush().unwrap();
+        let file = temp_file.as_file_mut();
+        file.seek(std::io::SeekFrom::Start(3)).unwrap();
+
+        for &algo in Algorithm::ALL {
+            // Offsets are absolute, matches crossing the range bounds are left out
+            assert_eq!(
+                find_in_file_range(file, 4..13, b"ab", algo).unwrap(),
+                vec![4, 8]
+            );
+            assert_eq!(
+                find_in_file_range(file, 5..9, b"ab", algo).unwrap(),
+                Vec::<usize>::new()
+            );
+            assert_eq!(
+                find_in_file_range(file, 12..100, b"ab", algo).unwrap(),
+                vec![12, 16]
+            );
+            assert_eq!(
+                find_in_file_range(file, 9..9, b"ab", algo).unwrap(),
+                Vec::<usize>::new()
+            );
+        }
+        assert!(find_in_file_range(file, 0..18, b"", Algorithm::Naive).is_err());
+        // Positioned reads leave the file cursor alone
+        #[cfg(unix)]
+        assert_eq!(file.stream_position().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_count_overlapping_and_non_overlapping() {
+        use crate::{count_in_file, count_in_file_with, SliceFinder};
+        use std::io::Write;
+
+        for &algo in Algorithm::ALL {
+            let finder = SliceFinder::new(b"aaaa", b"aa".to_vec()).unwrap();
+            // Overlapping is the default
+            assert_eq!(finder.count(algo), 3, "{algo}");
+            assert_eq!(finder.count_with(algo, true), 3, "{algo}");
+            assert_eq!(finder.count_with(algo, false), 2, "{algo}");
+
+            let finder = SliceFinder::new(b"abababa", b"aba".to_vec()).unwrap();
+            assert_eq!(finder.count(algo), 3, "{algo}");
+            assert_eq!(finder.count_with(algo, false), 2, "{algo}");
+        }
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"aaaa").unwrap();
+        temp_file.flush().unwrap();
+        assert_eq!(
+            count_in_file(temp_file.path(), b"aa", Algorithm::Bmh).unwrap(),
+            3
+        );
+        assert_eq!(
+            count_in_file_with(temp_file.path(), b"aa", Algorithm::Bmh, true).unwrap(),
+            3
+        );
+        assert_eq!(
+            count_in_file_with(temp_file.path(), b"aa", Algorithm::Bmh, false).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_from_env() {
+        use crate::{FinderError, FromEnvError};
+
+        // The only test touching these variables, so running in parallel is fine
+        std::env::remove_var("SIMD_NEEDLE_ALGO");
+        std::env::remove_var("SIMD_NEEDLE_BUFSIZE");
+        let finder = Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()).unwrap();
+        assert_eq!(finder.algorithm(), Algorithm::Naive);
+        assert_eq!(finder.buffer_size(), crate::DEFAULT_BUF_SIZE);
+
+        std::env::set_var("SIMD_NEEDLE_ALGO", "KMP");
+        std::env::set_var("SIMD_NEEDLE_BUFSIZE", "64");
+        let finder = Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()).unwrap();
+        assert_eq!(finder.algorithm(), Algorithm::Kmp);
+        assert_eq!(finder.buffer_size(), 64);
+        assert_eq!(finder.collect_all().unwrap(), vec![1]);
+
+        std::env::set_var("SIMD_NEEDLE_BUFSIZE", "1");
+        assert!(matches!(
+            Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()),
+            Err(FromEnvError::Finder(FinderError::BufferTooSmall))
+        ));
+
+        std::env::set_var("SIMD_NEEDLE_BUFSIZE", "8k");
+        let Err(err) = Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()) else {
+            panic!("8k accepted as buffer size");
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid value \"8k\" for SIMD_NEEDLE_BUFSIZE"
+        );
+
+        // Sizes that would only abort on allocation are rejected up front
+        std::env::set_var("SIMD_NEEDLE_BUFSIZE", "99999999999999");
+        assert!(matches!(
+            Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()),
+            Err(FromEnvError::InvalidVar {
+                name: "SIMD_NEEDLE_BUFSIZE",
+                ..
+            })
+        ));
+
+        std::env::remove_var("SIMD_NEEDLE_BUFSIZE");
+        std::env::set_var("SIMD_NEEDLE_ALGO", "fastest");
+        assert!(matches!(
+            Finder::from_env(Cursor::new(b"abc"), b"bc".to_vec()),
+            Err(FromEnvError::InvalidVar {
+                name: "SIMD_NEEDLE_ALGO",
+                ..
+            })
+        ));
+        std::env::remove_var("SIMD_NEEDLE_ALGO");
+    }
+
+    #[test]
+    fn test_peekable_finder() {
+        let finder = Finder::new(Cursor::new(b"ab.ab"), b"ab".to_vec(), None).unwrap();
+        let mut finder = finder.peekable();
+        assert_eq!(finder.peek_offset(), Some(0));
+        assert_eq!(finder.peek_offset(), Some(0));
+        assert_eq!(finder.next().unwrap().unwrap(), 0);
+        assert!(matches!(finder.peek(), Some(Ok(3))));
+        assert_eq!(finder.next().unwrap().unwrap(), 3);
+        assert!(finder.peek().is_none());
+        assert_eq!(finder.peek_offset(), None);
+        assert!(finder.next().is_none());
+
+        // Errors are peeked by reference and still yielded
+        let finder = Finder::new(FailingReader(b"..."), b"ab".to_vec(), None).unwrap();
+        let mut finder = finder.peekable();
+        assert!(matches!(finder.peek(), Some(Err(_))));
+        assert_eq!(finder.peek_offset(), None);
+        assert_eq!(
+            finder.next().unwrap().unwrap_err().to_string(),
+            "disk on fire"
+        );
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn test_mmap_finder_new_compressed() {
+        use crate::{Codec, MmapFinder, MmapFinderError};
+        use std::io::Write;
+
+        let original = b"compressed needle, another needle and a final needle".repeat(100);
+        let expected = crate::find_all_in_slice(&original, b"needle", Algorithm::Naive);
+
+        let mut codecs = Vec::new();
+        #[cfg(feature = "gzip")]
+        {
+            let gzip = |data: &[u8]| {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            };
+            codecs.push((Codec::Gzip, gzip(&original), gzip(b"")));
+        }
+        #[cfg(feature = "zstd")]
+        codecs.push((
+            Codec::Zstd,
+            zstd::encode_all(&original[..], 3).unwrap(),
+            zstd::encode_all(&b""[..], 3).unwrap(),
+        ));
+
+        for (codec, compressed, empty) in codecs {
+            // An empty stream decompresses to an empty mapping
+            let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+            temp_file.write_all(&empty).unwrap();
+            temp_file.flush().unwrap();
+            let finder =
+                MmapFinder::new_compressed(temp_file.path(), b"needle".to_vec(), codec).unwrap();
+            assert!(finder.as_bytes().is_empty(), "{codec:?}");
+            for &algo in Algorithm::ALL {
+                assert_eq!(finder.find_first(algo), None, "{codec:?}");
+                assert_eq!(finder.find_last(algo), None, "{codec:?}");
+                assert_eq!(finder.count(algo), 0, "{codec:?}");
+            }
+
+            let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+            temp_file.write_all(&compressed).unwrap();
+            temp_file.flush().unwrap();
+
+            let mut finder =
+                MmapFinder::new_compressed(temp_file.path(), b"needle".to_vec(), codec).unwrap();
+            assert_eq!(finder.as_bytes(), &original[..], "{codec:?}");
+            for &algo in Algorithm::ALL {
+                assert_eq!(
+                    finder.find_all(algo).collect::<Vec<_>>(),
+                    expected,
+                    "{codec:?}"
+                );
+            }
+            finder.set_needle(b"final".to_vec()).unwrap();
+            assert_eq!(
+                finder.find_first(Algorithm::Bmh),
+                crate::naive_search(&original, b"final")
+            );
+
+            // The uncompressed original is not valid compressed data
+            let mut plain_file = tempfile::NamedTempFile::new().unwrap();
+            plain_file.write_all(&original).unwrap();
+            plain_file.flush().unwrap();
+            assert!(matches!(
+                MmapFinder::new_compressed(plain_file.path(), b"needle".to_vec(), codec),
+                Err(MmapFinderError::Io(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_mmap_finder_rejects_non_regular_files() {
+        use crate::{MmapFinder, MmapFinderError};
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            MmapFinder::new(dir.path(), b"needle".to_vec()),
+            Err(MmapFinderError::NotARegularFile)
+        ));
+        assert!(matches!(
+            MmapFinder::new_populated(dir.path(), b"needle".to_vec()),
+            Err(MmapFinderError::NotARegularFile)
+        ));
+        #[cfg(feature = "gzip")]
+        assert!(matches!(
+            MmapFinder::new_compressed(dir.path(), b"needle".to_vec(), crate::Codec::Gzip),
+            Err(MmapFinderError::NotARegularFile)
+        ));
+
+        // Opening a FIFO for reading would block until a writer shows up
+        #[cfg(unix)]
+        {
+            let fifo = dir.path().join("fifo");
+            let path = std::ffi::CString::new(fifo.to_str().unwrap()).unwrap();
+            assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+            assert!(matches!(
+                MmapFinder::new(&fifo, b"needle".to_vec()),
+                Err(MmapFinderError::NotARegularFile)
+            ));
+            #[cfg(feature = "zstd")]
+            assert!(matches!(
+                MmapFinder::new_compressed(&fifo, b"needle".to_vec(), crate::Codec::Zstd),
+                Err(MmapFinderError::NotARegularFile)
+            ));
+        }
+
+        assert!(matches!(
+            MmapFinder::new(dir.path().join("missing"), b"needle".to_vec()),
+            Err(MmapFinderError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_mmap_finder_into_inner() {
+        use crate::MmapFinder;
+        use std::io::Write;
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"one two one two").unwrap();
+        temp_file.flush().unwrap();
+
+        let finder = MmapFinder::new(temp_file.path(), b"one".to_vec()).unwrap();
+        let (mmap, needle) = finder.into_inner();
+        assert_eq!(&mmap[..], b"one two one two");
+        assert_eq!(needle, b"one");
+
+        let finder = MmapFinder::from_mmap(mmap, needle).unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Naive).collect::<Vec<_>>(),
+            vec![0, 8]
+        );
+        let finder = MmapFinder::from_mmap(finder.into_inner().0, b"two".to_vec()).unwrap();
+        assert_eq!(
+            finder.find_all(Algorithm::Simd).collect::<Vec<_>>(),
+            vec![4, 12]
+        );
+    }
+
+    #[test]
+    fn test_find_all_parallel() {
+        use crate::{find_in_file_parallel, MmapFinder};
+        use std::io::Write;
+
+        // A few megabytes with matches straddling every 1MB seam
+        let seam = 1024 * 1024;
+        let mut haystack = b"the quick brown fox ".repeat(2 * seam / 20 + 123);
+        for k in 1..=2 {
+            haystack[k * seam - 3..k * seam + 3].copy_from_slice(b"needle");
+        }
+        haystack[..6].copy_from_slice(b"needle");
+        let len = haystack.len();
+        haystack[len - 6..].copy_from_slice(b"needle");
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(&haystack).unwrap();
+        temp_file.flush().unwrap();
+        let finder = MmapFinder::new(temp_file.path(), b"needle".to_vec()).unwrap();
+        let expected: Vec<usize> = finder.find_all(Algorithm::Naive).collect();
+        assert_eq!(expected.len(), 4);
+
+        for &algo in Algorithm::ALL {
+            assert_eq!(finder.find_all_parallel(algo), expected, "{algo}");
+            // Seams right inside, before and after matches
+            for chunk_len in [seam - 3, seam + 2, seam + 3] {
+                assert_eq!(
+                    finder.find_all_in_chunks(algo, chunk_len),
+                    expected,
+                    "{algo} {chunk_len}"
+                );
+            }
+        }
+        assert_eq!(
+            find_in_file_parallel(temp_file.path(), b"needle", Algorithm::Bmh).unwrap(),
+            expected
+        );
+
+        // Overlapping matches across a seam
+        let finder = crate::SliceFinder::new(b"aaaaaa", b"aaa".to_vec()).unwrap();
+        let expected: Vec<usize> = finder.find_all(Algorithm::Naive).collect();
+        let mut small = tempfile::NamedTempFile::new().unwrap();
+        small.write_all(b"aaaaaa").unwrap();
+        small.flush().unwrap();
+        let finder = MmapFinder::new(small.path(), b"aaa".to_vec()).unwrap();
+        for chunk_len in 1..=6 {
+            assert_eq!(
+                finder.find_all_in_chunks(Algorithm::Kmp, chunk_len),
+                expected
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_in_file_parallel_streams_fifos() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("fifo");
+        let path = std::ffi::CString::new(fifo.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+
+        let writer_path = fifo.clone();
+        let writer = std::thread::spawn(move || {
+            let mut fifo = std::fs::OpenOptions::new()
+                .write(true)
+                .open(writer_path)
+                .unwrap();
+            fifo.write_all(b"pipe needle pipe needle").unwrap();
+        });
+        let found = crate::find_in_file_parallel(&fifo, b"needle", Algorithm::Simd).unwrap();
+        writer.join().unwrap();
+        assert_eq!(found, vec![5, 17]);
+    }
+
+    #[test]
+    fn test_run_reports_stream_length() {
+        let haystack = b"needle in a haystack with another needle at the end".repeat(500);
+        for &algo in Algorithm::ALL {
+            for buffer_size in [6, 64, 8192] {
+                let finder = Finder::with_buffer_size(
+                    Cursor::new(&haystack),
+                    b"needle".to_vec(),
+                    buffer_size,
+                    Some(algo),
+                )
+                .unwrap();
+                let (positions, len) = finder.run().unwrap();
+                assert_eq!(
+                    positions,
+                    find_all(&haystack, b"needle", Algorithm::Naive),
+                    "{algo} {buffer_size}"
+                );
+                assert_eq!(len, haystack.len(), "{algo} {buffer_size}");
+            }
+        }
+
+        let finder = Finder::new(Cursor::new(b""), b"needle".to_vec(), None).unwrap();
+        assert_eq!(finder.run().unwrap(), (Vec::new(), 0));
+
+        let finder = Finder::new(FailingReader(b"needle"), b"needle".to_vec(), None).unwrap();
+        assert_eq!(finder.run().unwrap_err().to_string(), "disk on fire");
+    }
+
+    #[test]
+    fn test_context_finder_window_stays_bounded_on_dense_matches() {
+        // Every byte matches, so the window is never left without a match in it
+        let haystack = vec![b'a'; 8 * DEFAULT_BUF_SIZE];
+        let (needle, context) = (b"aa".to_vec(), 8);
+        let mut finder =
+            Finder::with_context(&haystack[..], needle.clone(), context, None).unwrap();
+        let mut count = 0;
+        let mut max_window = 0;
+        while let Some(m) = finder.next() {
+            let m = m.unwrap();
+            assert_eq!(m.offset, count as u64);
+            count += 1;
+            max_window = max_window.max(finder.window_len());
+        }
+        assert_eq!(count, haystack.len() - 1);
+        assert!(max_window <= DEFAULT_BUF_SIZE + needle.len() + 2 * context);
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn test_decompress_to_file_limit() {
+        use crate::mmap_finder::decompress_to_file;
+        use std::io::{Read, Seek};
+
+        let mut file = decompress_to_file(&b"0123456789"[..], 10).unwrap();
+        let mut contents = Vec::new();
+        file.rewind().unwrap();
+        file.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"0123456789");
+
+        // A stream that keeps producing data stops one byte past the limit
+        let err = decompress_to_file(std::io::repeat(0), 1024).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
 }